@@ -25,6 +25,7 @@ impl NcBlitter {
         let data_ptr: *const c_void = data as *const _ as *const c_void;
         let res = unsafe { c_api::ncblit_rgba(data_ptr, line_size as i32, vopts) };
         error![
+            @fn "ncblit_rgba",
             res,
             &format!["NcBlitter::blit_rgba(data, {}, {:?})", line_size, vopts],
             res as usize
@@ -38,6 +39,7 @@ impl NcBlitter {
         let data_ptr: *const c_void = data as *const _ as *const c_void;
         let res = unsafe { c_api::ncblit_bgrx(data_ptr, line_size as i32, vopts) };
         error![
+            @fn "ncblit_bgrx",
             res,
             &format!["NcBlitter::blit_bgrx(data, {}, {:?})", line_size, vopts],
             res as usize
@@ -61,6 +63,7 @@ impl NcBlitter {
         let res =
             unsafe { c_api::ncblit_rgb_packed(data_ptr, line_size as i32, vopts, alpha as i32) };
         error![
+            @fn "ncblit_rgb_packed",
             res,
             &format![
                 "NcBlitter::blit_rgb_packed(data, {}, {:?}, {})",
@@ -86,6 +89,7 @@ impl NcBlitter {
         let res =
             unsafe { c_api::ncblit_rgb_loose(data_ptr, line_size as i32, vopts, alpha as i32) };
         error![
+            @fn "ncblit_rgb_loose",
             res,
             &format![
                 "NcBlitter::blit_rgb_loose(data, {}, {:?}, {})",
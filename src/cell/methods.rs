@@ -2,8 +2,10 @@
 
 use crate::{
     c_api::{self, nccell_load, NcChannels_u64, NCRESULT_ERR},
-    cstring, error, rstring, NcAlpha, NcCell, NcChannels, NcError, NcPaletteIndex, NcPlane,
-    NcResult, NcRgb, NcStyle,
+    cstring, error,
+    error::clear_errno,
+    rstring, NcAlpha, NcCell, NcChannels, NcError, NcPaletteIndex, NcPlane, NcResult, NcRgb,
+    NcStyle,
 };
 
 #[allow(unused_imports)] // for doc comments
@@ -67,6 +69,7 @@ impl NcCell {
     pub fn load(plane: &mut NcPlane, cell: &mut NcCell, egc: &str) -> NcResult<u32> {
         let bytes = unsafe { c_api::nccell_load(plane, cell, cstring![egc]) };
         error![
+            @fn "nccell_load",
             bytes,
             &format!["NcCell.load(NcPlane, NcCell, {:?})", egc],
             bytes as u32
@@ -89,8 +92,9 @@ impl NcCell {
         style: NcStyle,
         channels: NcChannels,
     ) -> NcResult<u32> {
+        clear_errno();
         let bytes = c_api::nccell_prime(plane, cell, gcluster, style.into(), channels.into());
-        error![bytes, "", bytes as u32]
+        error![@fn "nccell_prime", bytes, "", bytes as u32]
     }
 
     /// Duplicate this `NcCell` into another one.
@@ -100,8 +104,9 @@ impl NcCell {
     /// *C style function: [nccell_duplicate()][c_api::nccell_duplicate].*
     pub fn duplicate(&self, common_plane: &mut NcPlane) -> NcResult<NcCell> {
         let mut target = NcCell::new();
+        clear_errno();
         let res = unsafe { c_api::nccell_duplicate(common_plane, &mut target, self) };
-        error![res, "NcCell.duplicate()", target]
+        error![@fn "nccell_duplicate", res, "NcCell.duplicate()", target]
     }
 
     /// Initializes (zeroes out) this `NcCell`.
@@ -392,7 +397,7 @@ impl NcCell {
     /// This pointer can be invalidated by any further operation on the referred
     /// plane, so… watch out!
     ///
-    /// *C style function: [nccell_extended_gcluster()][c_api::nccell_wide_left_p].*
+    /// *C style function: [nccell_extended_gcluster()][c_api::nccell_extended_gcluster].*
     pub fn extended_gcluster(&self, plane: &NcPlane) -> &str {
         let egcpointer = unsafe { c_api::nccell_extended_gcluster(plane, self) };
         rstring![egcpointer]
@@ -420,7 +425,7 @@ impl NcCell {
     ///
     /// *C style function: [nccell_wide_left_p()][c_api::nccell_wide_left_p].*
     pub fn wide_left_p(&self) -> bool {
-        c_api::nccell_wide_right_p(self)
+        c_api::nccell_wide_left_p(self)
     }
 
     /// Is this the right side of a wide character?
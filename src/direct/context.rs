@@ -0,0 +1,204 @@
+//! `NcDirectContext`
+
+use core::{
+    ptr::{null, null_mut, NonNull},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{
+    c_api, error::clear_errno, panic_hook, NcDirect, NcDirectFlags, NcError, NcErrorKind, NcResult,
+    NcStyle,
+};
+
+/// Whether there's currently an [`NcDirectContext`] alive in this process.
+static DIRECT_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// An owned direct mode context, that restores the terminal when dropped.
+///
+/// It dereferences to [`NcDirect`], so all of its methods are available.
+///
+/// When dropped, including while unwinding from a panic, it resets the
+/// foreground and background colors and the styles to their defaults,
+/// enables the cursor again, flushes the output and then stops the context.
+///
+/// Only one `NcDirectContext` can be alive at a time. Trying to create a new
+/// one while another one exists returns an error.
+///
+/// # Example
+/// ```no_run
+/// use libnotcurses_sys::*;
+///
+/// # fn main() -> NcResult<()> {
+/// let mut ncd = NcDirectContext::new()?;
+/// ncd.set_fg_rgb(0xff8800)?;
+/// ncd.styles_on(NcStyle::Bold)?;
+/// println!("this is bold and orange");
+/// // the colors and styles are reset here
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct NcDirectContext {
+    ncd: NonNull<NcDirect>,
+    panic_hook: bool,
+}
+
+/// # `NcDirectContext` Constructors and destructors
+impl NcDirectContext {
+    /// New direct mode context with the default options.
+    ///
+    /// *C style function: [ncdirect_init()][c_api::ncdirect_init].*
+    pub fn new() -> NcResult<Self> {
+        Self::with_flags(NcDirectFlags::None)
+    }
+
+    /// New direct mode context, expects [`NcDirectFlags`].
+    ///
+    /// Returns an error if there's already another `NcDirectContext` alive.
+    ///
+    /// *C style function: [ncdirect_init()][c_api::ncdirect_init].*
+    pub fn with_flags(flags: NcDirectFlags) -> NcResult<Self> {
+        Self::init(flags, false)
+    }
+
+    /// New direct mode context, expects [`NcDirectFlags`], and installs a
+    /// panic hook that restores the terminal before the panic message gets
+    /// printed.
+    ///
+    /// When panicking while this context is alive, the hook restores the
+    /// colors, styles and cursor, and the terminal modes from before the
+    /// context was created, and then delegates to the previous panic hook.
+    /// The context is stopped afterwards, when it's dropped while unwinding,
+    /// which doesn't happen with `panic = "abort"`.
+    ///
+    /// *C style function: [ncdirect_init()][c_api::ncdirect_init].*
+    pub fn with_panic_hook(flags: NcDirectFlags) -> NcResult<Self> {
+        Self::init(flags, true)
+    }
+
+    fn init(flags: NcDirectFlags, panic_hook: bool) -> NcResult<Self> {
+        if DIRECT_ACTIVE
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return Err(NcError::with_kind(
+                NcErrorKind::InvalidArgument,
+                "NcDirectContext.with_flags(): another NcDirectContext is already active",
+            ));
+        }
+        let modes = panic_hook.then(panic_hook::TermModes::save);
+        clear_errno();
+        let res = unsafe { c_api::ncdirect_init(null(), null_mut(), flags.into()) };
+        match NonNull::new(res) {
+            Some(ncd) => {
+                if let Some(modes) = modes {
+                    panic_hook::register_ncd(ncd.as_ptr(), modes);
+                }
+                Ok(Self { ncd, panic_hook })
+            }
+            None => {
+                DIRECT_ACTIVE.store(false, Ordering::Release);
+                Err(NcError::null_returned(
+                    "ncdirect_init",
+                    "NcDirectContext.with_flags()",
+                ))
+            }
+        }
+    }
+
+    /// Restores the terminal and stops the context, consuming it.
+    ///
+    /// Dropping the context does the same, but then any error gets ignored.
+    ///
+    /// *C style function: [ncdirect_stop()][c_api::ncdirect_stop].*
+    pub fn stop(mut self) -> NcResult<()> {
+        let ncd = self.ncd.as_ptr();
+        if self.panic_hook {
+            panic_hook::unregister_ncd(ncd);
+        }
+        let restored = self.restore();
+        core::mem::forget(self);
+        clear_errno();
+        let res = unsafe { c_api::ncdirect_stop(ncd) };
+        DIRECT_ACTIVE.store(false, Ordering::Release);
+        restored?;
+        if res < c_api::NCRESULT_OK {
+            return Err(NcError::negative_return(
+                "ncdirect_stop",
+                res,
+                "NcDirectContext.stop()",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// # `NcDirectContext` methods
+impl NcDirectContext {
+    /// Returns the raw pointer to the wrapped [`NcDirect`].
+    ///
+    /// The pointer is valid for as long as this context is alive.
+    pub fn as_ptr(&self) -> *mut NcDirect {
+        self.ncd.as_ptr()
+    }
+
+    /// Returns true if there's currently an `NcDirectContext` alive.
+    pub fn is_active() -> bool {
+        DIRECT_ACTIVE.load(Ordering::Acquire)
+    }
+
+    /// Resets the colors and styles to their defaults, enables the cursor
+    /// and flushes the output.
+    ///
+    /// All the steps are attempted even if some of them fail,
+    /// and the first error is returned.
+    ///
+    /// *(No equivalent C style function)*
+    pub fn restore(&mut self) -> NcResult<()> {
+        let ncd: &mut NcDirect = self;
+        let results = [
+            ncd.set_fg_default(),
+            ncd.set_bg_default(),
+            ncd.styles_set(NcStyle::None),
+            ncd.cursor_enable(),
+            ncd.flush(),
+        ];
+        results.into_iter().collect()
+    }
+}
+
+mod std_impls {
+    use super::{NcDirectContext, DIRECT_ACTIVE};
+    use crate::{c_api, panic_hook, NcDirect};
+    use core::{
+        ops::{Deref, DerefMut},
+        sync::atomic::Ordering,
+    };
+
+    impl Deref for NcDirectContext {
+        type Target = NcDirect;
+
+        fn deref(&self) -> &NcDirect {
+            unsafe { self.ncd.as_ref() }
+        }
+    }
+
+    impl DerefMut for NcDirectContext {
+        fn deref_mut(&mut self) -> &mut NcDirect {
+            unsafe { self.ncd.as_mut() }
+        }
+    }
+
+    impl Drop for NcDirectContext {
+        fn drop(&mut self) {
+            let ncd = self.ncd.as_ptr();
+            if self.panic_hook {
+                panic_hook::unregister_ncd(ncd);
+            }
+            // this also runs while unwinding, so it must not panic
+            let _ = self.restore();
+            let _ = unsafe { c_api::ncdirect_stop(ncd) };
+            DIRECT_ACTIVE.store(false, Ordering::Release);
+        }
+    }
+}
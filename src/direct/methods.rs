@@ -3,9 +3,9 @@
 use core::ptr::{null, null_mut};
 
 use crate::{
-    c_api, cstring, error, error_ref_mut, rstring_free, NcAlign, NcBlitter, NcCapabilities,
-    NcChannels, NcDirect, NcDirectFlags, NcError, NcInput, NcPaletteIndex, NcPlane, NcResult,
-    NcRgb, NcScale, NcStyle, NcTime,
+    c_api, cstring, error, error::clear_errno, error_ref_mut, rstring_free, NcAlign, NcBlitter,
+    NcCapabilities, NcChannels, NcDirect, NcDirectFlags, NcError, NcEvents, NcInput,
+    NcPaletteIndex, NcPlane, NcResult, NcRgb, NcScale, NcStyle, NcTime,
 };
 
 /// # `NcDirect` constructors and destructors
@@ -600,9 +600,41 @@ impl NcDirect {
             ninput = null_mut();
         }
 
+        clear_errno();
         let res = unsafe { c_api::ncdirect_get(self, ntime, ninput) };
-        core::char::from_u32(res)
-            .ok_or_else(|| NcError::with_msg(res as i32, &format!["Nc.get(time: {:?})", time]))
+        core::char::from_u32(res).ok_or_else(|| {
+            NcError::negative_return(
+                "ncdirect_get",
+                res as i32,
+                &format!["NcDirect.get(time: {:?})", time],
+            )
+        })
+    }
+
+    /// Returns an iterator over the input [`NcEvent`]s.
+    ///
+    /// See [`Nc::events`][crate::Nc#method.events].
+    ///
+    /// *(No equivalent C style function)*
+    ///
+    /// [`NcEvent`]: crate::NcEvent
+    pub fn events(&mut self, timeout: Option<NcTime>) -> NcEvents<&mut NcDirect> {
+        NcEvents::new(self, timeout)
+    }
+
+    /// Returns an asynchronous stream of the input [`NcEvent`]s.
+    ///
+    /// See [`NcEventStream`][crate::NcEventStream] for details.
+    ///
+    /// *Requires the `async` feature.*
+    ///
+    /// *(No equivalent C style function)*
+    ///
+    /// [`NcEvent`]: crate::NcEvent
+    #[cfg(all(feature = "async", unix))]
+    pub fn event_stream(&mut self) -> NcResult<crate::NcEventStream<&mut NcDirect>> {
+        let fd = self.inputready_fd()?;
+        unsafe { crate::NcEventStream::new(self, fd) }
     }
 
     /// Reads input blocking until an event is processed or a signal is received.
@@ -613,9 +645,11 @@ impl NcDirect {
     ///
     /// *C style function: [ncdirect_get_blocking()][c_api::ncdirect_get_blocking].*
     pub fn get_blocking(&mut self, input: Option<&mut NcInput>) -> NcResult<char> {
+        clear_errno();
         let res = c_api::ncdirect_get_blocking(self, input);
-        core::char::from_u32(res as u32)
-            .ok_or_else(|| NcError::with_msg(res, "NcDirect.get_blocking()"))
+        core::char::from_u32(res as u32).ok_or_else(|| {
+            NcError::negative_return("ncdirect_get_blocking", res, "NcDirect.get_blocking()")
+        })
     }
 
     /// Reads input without blocking.
@@ -626,9 +660,11 @@ impl NcDirect {
     ///
     /// *C style function: [ncdirect_get_nblock()][c_api::ncdirect_get_nblock].*
     pub fn get_nblock(&mut self, input: Option<&mut NcInput>) -> NcResult<char> {
+        clear_errno();
         let res = c_api::ncdirect_get_nblock(self, input);
-        core::char::from_u32(res as u32)
-            .ok_or_else(|| NcError::with_msg(res, "NcDirect.get_nblock()"))
+        core::char::from_u32(res as u32).ok_or_else(|| {
+            NcError::negative_return("ncdirect_get_nblock", res, "NcDirect.get_nblock()")
+        })
     }
 
     /// Get a file descriptor suitable for input event poll()ing.
@@ -640,8 +676,10 @@ impl NcDirect {
     /// with stdin (but it might be!).
     ///
     /// *C style function: [ncdirect_inputready_fd()][c_api::ncdirect_inputready_fd].*
-    pub fn inputready_fd(&mut self) -> NcResult<()> {
-        error![unsafe { c_api::ncdirect_inputready_fd(self) }]
+    pub fn inputready_fd(&mut self) -> NcResult<i32> {
+        clear_errno();
+        let res = unsafe { c_api::ncdirect_inputready_fd(self) };
+        error![@fn "ncdirect_inputready_fd", res, "NcDirect.inputready_fd()", res]
     }
 
     /// Outputs the `string` according to the `channels`, and
@@ -81,9 +81,12 @@
 #[cfg(test)]
 mod test;
 
+mod context;
 mod methods;
 pub(crate) mod reimplemented;
 
+pub use context::NcDirectContext;
+
 use c_api::NcDirectFlags_u64;
 
 /// Minimal notcurses instance for styling text.
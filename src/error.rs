@@ -1,53 +1,286 @@
 //! Error handling with `NcError`, `NcResult` & `NcResult_i32`
 
+use std::{error, sync::Arc};
+
 /// The result type for the Rust methods API.
 pub type NcResult<T> = Result<T, NcError>;
 
 /// The error type for the Rust methods API.
+///
+/// Besides the returned integer and a descriptive message, it stores the
+/// [`kind`][NcErrorKind] of failure, the name of the C function that failed
+/// (when known), the captured `errno` (when set), and the underlying error
+/// that caused it, if any, accessible through [`source`].
+///
+/// [`source`]: std::error::Error::source
 #[derive(Debug, Clone, Default)]
 pub struct NcError {
+    /// The integer result returned by the C function,
+    /// or [`NCRESULT_ERR`][c_api::NCRESULT_ERR].
     pub int: c_api::NcResult_i32,
+
+    /// A message describing the error.
     pub msg: String,
+
+    /// The kind of error.
+    pub kind: NcErrorKind,
+
+    /// The name of the C function that failed, or an empty string if unknown.
+    pub function: &'static str,
+
+    /// The value of `errno` captured right after the failing call, if set.
+    ///
+    /// It's only captured when `errno` was cleared right before the call,
+    /// so it's never a stale value left by a previous call.
+    pub errno: Option<i32>,
+
+    /// The underlying cause of this error.
+    source: Option<Arc<dyn error::Error + Send + Sync + 'static>>,
 }
 
-/// # Methods
+/// The kind of an [`NcError`].
+///
+/// # Default
+/// *[`NcErrorKind::Other`]
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NcErrorKind {
+    /// A C function returned a null pointer.
+    NullReturned,
+
+    /// A C function returned a negative integer.
+    NegativeReturn,
+
+    /// An argument was not valid (e.g. a string containing a nul byte,
+    /// or a value out of range).
+    InvalidArgument,
+
+    /// An input/output error.
+    Io,
+
+    /// The operation is not supported (e.g. by the terminal).
+    Unsupported,
+
+    /// Any other error.
+    Other,
+}
+
+/// # Constructors
 impl NcError {
     /// New NcError with default [`NCRESULT_ERR`][c_api::NCRESULT_ERR]
     /// error number, and no message.
     pub fn new() -> Self {
-        Self { int: c_api::NCRESULT_ERR, ..Default::default() }
+        Self::new_err(c_api::NCRESULT_ERR)
     }
 
     /// New NcError with custom error number, and without message.
     pub fn new_err(int: c_api::NcResult_i32) -> Self {
-        Self { int, ..Default::default() }
+        Self { int, kind: NcErrorKind::from_int(int), ..Default::default() }
     }
 
     /// New NcError with default [`NCRESULT_ERR`][c_api::NCRESULT_ERR]
     /// error number and a custom message.
     pub fn new_msg(msg: &str) -> Self {
-        Self { int: c_api::NCRESULT_ERR, msg: msg.to_string() }
+        Self::with_msg(c_api::NCRESULT_ERR, msg)
     }
 
     /// New NcError with both a custom error number and a custom message.
     pub fn with_msg(int: c_api::NcResult_i32, msg: &str) -> Self {
-        Self { int, msg: msg.to_string() }
+        Self { int, msg: msg.to_string(), kind: NcErrorKind::from_int(int), ..Default::default() }
+    }
+
+    /// New NcError of the given `kind`, with a custom message.
+    pub fn with_kind(kind: NcErrorKind, msg: &str) -> Self {
+        Self { int: c_api::NCRESULT_ERR, msg: msg.to_string(), kind, ..Default::default() }
+    }
+
+    /// New [`NullReturned`][NcErrorKind::NullReturned] error for the C
+    /// `function`, capturing `errno`.
+    ///
+    /// See [`with_errno`][NcError#method.with_errno].
+    pub fn null_returned(function: &'static str, msg: &str) -> Self {
+        Self::with_kind(NcErrorKind::NullReturned, msg)
+            .with_function(function)
+            .with_errno()
+    }
+
+    /// New [`NegativeReturn`][NcErrorKind::NegativeReturn] error for the C
+    /// `function`, with its returned integer, capturing `errno`.
+    ///
+    /// See [`with_errno`][NcError#method.with_errno].
+    pub fn negative_return(function: &'static str, int: c_api::NcResult_i32, msg: &str) -> Self {
+        Self::with_msg(int, msg)
+            .with_function(function)
+            .with_errno()
+    }
+}
+
+/// # Methods
+impl NcError {
+    /// Sets the name of the C function that failed.
+    pub fn with_function(mut self, function: &'static str) -> Self {
+        self.function = function;
+        self
+    }
+
+    /// Captures the current value of `errno`, if it's set.
+    ///
+    /// Since successful calls don't reset `errno`, it must have been cleared
+    /// right before the failing call, or else it could be a stale value.
+    pub fn with_errno(mut self) -> Self {
+        self.errno = match std::io::Error::last_os_error().raw_os_error() {
+            Some(0) | None => None,
+            errno => errno,
+        };
+        self
+    }
+
+    /// Sets the underlying cause of this error.
+    pub fn with_source<E>(mut self, source: E) -> Self
+    where
+        E: error::Error + Send + Sync + 'static,
+    {
+        self.source = Some(Arc::new(source));
+        self
+    }
+
+    /// Returns the kind of error.
+    pub fn kind(&self) -> NcErrorKind {
+        self.kind
+    }
+
+    /// Returns the captured `errno` as an [`io::Error`][std::io::Error], if any.
+    pub fn os_error(&self) -> Option<std::io::Error> {
+        self.errno.map(std::io::Error::from_raw_os_error)
+    }
+}
+
+impl NcErrorKind {
+    /// Returns the kind corresponding to the integer returned by a C function.
+    fn from_int(int: c_api::NcResult_i32) -> Self {
+        if int < c_api::NCRESULT_OK {
+            Self::NegativeReturn
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// Clears `errno`, before calling a C function whose failure
+/// is going to be reported with [`NcError::with_errno`].
+pub(crate) fn clear_errno() {
+    #[cfg(any(target_os = "linux", target_os = "emscripten", target_os = "redox"))]
+    unsafe {
+        *libc::__errno_location() = 0;
+    }
+    #[cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "dragonfly"
+    ))]
+    unsafe {
+        *libc::__error() = 0;
+    }
+    #[cfg(any(target_os = "android", target_os = "netbsd", target_os = "openbsd"))]
+    unsafe {
+        *libc::__errno() = 0;
     }
 }
 
+/// Returns the name of the first C function called in the stringified
+/// expression `expr`, or an empty string if there's none.
+///
+/// Used by the error macros to find out which C function failed.
+pub(crate) fn c_function_name(expr: &'static str) -> &'static str {
+    let bytes = expr.as_bytes();
+    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let mut start = 0;
+    while start < bytes.len() {
+        if !is_ident(bytes[start]) {
+            start += 1;
+            continue;
+        }
+        let mut end = start;
+        while end < bytes.len() && is_ident(bytes[end]) {
+            end += 1;
+        }
+        let ident = &expr[start..end];
+        let rest = expr[end..].trim_start();
+        if rest.starts_with('(')
+            && (ident.starts_with("nc") || ident.starts_with("notcurses_"))
+            && ident.contains('_')
+        {
+            return ident;
+        }
+        start = end;
+    }
+    ""
+}
+
 mod std_impls {
-    use super::NcError;
-    use std::{self, error, fmt};
+    use super::{NcError, NcErrorKind};
+    use std::{self, error, ffi, fmt, io, str};
+
+    impl Default for NcErrorKind {
+        fn default() -> Self {
+            Self::Other
+        }
+    }
+
+    impl fmt::Display for NcErrorKind {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let s = match self {
+                Self::NullReturned => "null returned",
+                Self::NegativeReturn => "negative return",
+                Self::InvalidArgument => "invalid argument",
+                Self::Io => "input/output error",
+                Self::Unsupported => "unsupported",
+                Self::Other => "other error",
+            };
+            write!(f, "{}", s)
+        }
+    }
 
     impl fmt::Display for NcError {
         fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-            write!(f, "NcError {}: {}", self.int, self.msg)
+            write!(f, "NcError {}: {}", self.int, self.msg)?;
+            if !self.function.is_empty() {
+                write!(f, " [{}]", self.function)?;
+            }
+            if let Some(errno) = self.errno {
+                write!(f, " (errno {})", errno)?;
+            }
+            Ok(())
         }
     }
 
     impl error::Error for NcError {
-        fn description(&self) -> &str {
-            &self.msg
+        fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+            match &self.source {
+                Some(source) => Some(source.as_ref()),
+                None => None,
+            }
+        }
+    }
+
+    impl From<io::Error> for NcError {
+        fn from(e: io::Error) -> Self {
+            let mut err = NcError::with_kind(NcErrorKind::Io, &e.to_string());
+            err.errno = e.raw_os_error();
+            err.with_source(e)
+        }
+    }
+
+    impl From<ffi::NulError> for NcError {
+        fn from(e: ffi::NulError) -> Self {
+            NcError::with_kind(NcErrorKind::InvalidArgument, &e.to_string()).with_source(e)
+        }
+    }
+
+    impl From<str::Utf8Error> for NcError {
+        fn from(e: str::Utf8Error) -> Self {
+            NcError::with_kind(NcErrorKind::InvalidArgument, &e.to_string()).with_source(e)
         }
     }
 }
@@ -74,3 +307,49 @@ pub(crate) mod c_api {
     /// [`NcResult_i32`] MAX value.
     pub const NCRESULT_MAX: i32 = i32::MAX;
 }
+
+#[cfg(test)]
+mod test {
+    use super::{c_function_name, clear_errno, NcError, NcErrorKind};
+    use std::error::Error;
+
+    #[test]
+    fn c_function_names() {
+        assert_eq!(
+            "ncplane_destroy",
+            c_function_name("unsafe { c_api :: ncplane_destroy(self) }")
+        );
+        assert_eq!(
+            "notcurses_render",
+            c_function_name("c_api::notcurses_render (self)")
+        );
+        assert_eq!("", c_function_name("res"));
+        assert_eq!("", c_function_name("self.render()"));
+    }
+
+    #[test]
+    fn kinds_and_sources() {
+        assert_eq![
+            NcErrorKind::NegativeReturn,
+            NcError::with_msg(-1, "").kind()
+        ];
+        // like NCRESULT_ERR
+        assert_eq![NcErrorKind::NegativeReturn, NcError::new().kind()];
+        assert_eq![NcErrorKind::NegativeReturn, NcError::new_msg("").kind()];
+        assert_eq![NcErrorKind::Other, NcError::new_err(1).kind()];
+
+        let io = std::io::Error::from_raw_os_error(2);
+        let err = NcError::from(io);
+        assert_eq![NcErrorKind::Io, err.kind()];
+        assert_eq![Some(2), err.errno];
+        assert![err.source().is_some()];
+    }
+
+    #[test]
+    fn errno_cleared() {
+        // a failing call leaves errno set
+        assert![std::fs::File::open("/nonexistent/notcurses").is_err()];
+        clear_errno();
+        assert_eq![None, NcError::new().with_errno().errno];
+    }
+}
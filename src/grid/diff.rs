@@ -0,0 +1,266 @@
+//! `NcGridDiff`
+
+use core::slice::Iter;
+
+use crate::{
+    grid::{NcGrid, NcGridCell},
+    NcAlpha, NcChannels, NcPlane, NcPlaneHandle, NcResult, NcRgb, NcStyle,
+};
+
+/// A cell that differs between two [`NcGrid`]s.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NcGridChange {
+    /// The row of the cell.
+    pub y: u32,
+
+    /// The column of the cell.
+    pub x: u32,
+
+    /// The cell in the old grid, or a blank one if it was out of its bounds.
+    pub old: NcGridCell,
+
+    /// The cell in the new grid, or a blank one if it's out of its bounds.
+    pub new: NcGridCell,
+}
+
+/// The cells that differ between an old and a new [`NcGrid`].
+///
+/// Two cells differ if they have a different glyph, width, styles or
+/// channels, including their alpha. When the grids have different dimensions, the
+/// cells beyond the bounds of one of them are compared against blank cells.
+///
+/// # Example
+/// ```no_run
+/// # use libnotcurses_sys::*;
+/// # fn main() -> NcResult<()> {
+/// # let nc = unsafe { Nc::new()? };
+/// # let plane = unsafe { nc.stdplane() };
+/// let before = plane.contents_grid(Some(0), Some(0), None, None)?;
+/// plane.putstr_yx(Some(0), Some(0), "hello")?;
+/// let after = plane.contents_grid(Some(0), Some(0), None, None)?;
+///
+/// let diff = before.diff(&after);
+/// assert_eq![5, diff.len()];
+/// println!("{}", diff);
+/// # unsafe { nc.stop()? };
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct NcGridDiff {
+    old_dim: (u32, u32),
+    new_dim: (u32, u32),
+    changes: Vec<NcGridChange>,
+}
+
+/// # Diffing
+impl NcGrid {
+    /// Returns the cells that differ between this grid and a `new` one.
+    pub fn diff(&self, new: &NcGrid) -> NcGridDiff {
+        let rows = self.rows.max(new.rows);
+        let cols = self.cols.max(new.cols);
+        let changes = (0..rows)
+            .flat_map(|y| (0..cols).map(move |x| (y, x)))
+            .filter_map(|(y, x)| {
+                let (old, new) = (self.cell_at(y, x), new.cell_at(y, x));
+                if same_cell(&old, &new) {
+                    None
+                } else {
+                    Some(NcGridChange { y, x, old, new })
+                }
+            })
+            .collect();
+        NcGridDiff { old_dim: self.dim_yx(), new_dim: new.dim_yx(), changes }
+    }
+}
+
+/// # Methods
+impl NcGridDiff {
+    /// Returns true if no cell differs, and the dimensions are the same.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.old_dim == self.new_dim
+    }
+
+    /// Returns the number of cells that differ.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Returns the cells that differ, row by row.
+    pub fn changes(&self) -> &[NcGridChange] {
+        &self.changes
+    }
+
+    /// Returns an iterator over the cells that differ, row by row.
+    pub fn iter(&self) -> Iter<'_, NcGridChange> {
+        self.changes.iter()
+    }
+
+    /// Returns the rows and columns of the old grid.
+    pub fn old_dim_yx(&self) -> (u32, u32) {
+        self.old_dim
+    }
+
+    /// Returns the rows and columns of the new grid.
+    pub fn new_dim_yx(&self) -> (u32, u32) {
+        self.new_dim
+    }
+
+    /// Returns the rows and columns covering both grids.
+    pub fn dim_yx(&self) -> (u32, u32) {
+        (
+            self.old_dim.0.max(self.new_dim.0),
+            self.old_dim.1.max(self.new_dim.1),
+        )
+    }
+
+    /// Creates a child plane of `parent` at the offset `y`×`x`, highlighting
+    /// the cells that differ, and returns it.
+    ///
+    /// The differing cells are drawn with their new glyph, styles and
+    /// foreground, over a `highlight` background. The rest of the plane is
+    /// transparent, so the planes below it are seen through.
+    ///
+    /// Place it over the plane the new grid came from, in order to see which
+    /// of its cells have changed. The returned handle borrows `parent`, and
+    /// the plane must be destroyed when no longer needed.
+    pub fn overlay<'parent, RGB: Into<NcRgb>>(
+        &self,
+        parent: &'parent NcPlaneHandle<'_>,
+        y: i32,
+        x: i32,
+        highlight: RGB,
+    ) -> NcResult<NcPlaneHandle<'parent>> {
+        let (rows, cols) = self.dim_yx();
+        let mut overlay = parent.new_child_sized(y, x, rows.max(1), cols.max(1))?;
+
+        let mut transparent = NcChannels::with_default();
+        transparent.set_fg_alpha(NcAlpha::Transparent)?;
+        transparent.set_bg_alpha(NcAlpha::Transparent)?;
+        overlay.set_base("", NcStyle::None, transparent)?;
+
+        let highlight = highlight.into();
+        for change in self.changes.iter().filter(|c| !c.new.wide_right_p()) {
+            let mut channels = change.new.channels;
+            channels.set_bg_rgb(highlight);
+            overlay.set_channels(channels);
+            overlay.set_styles(change.new.style);
+            let egc = if change.new.egc.is_empty() { " " } else { change.new.egc.as_str() };
+            overlay.putstr_yx(Some(change.y), Some(change.x), egc)?;
+        }
+        Ok(overlay)
+    }
+}
+
+/// # Diffing
+impl NcPlane {
+    /// Returns the cells that differ between this `NcPlane` and a `new` one.
+    ///
+    /// The whole of both planes is compared, cell by cell, from their origin.
+    /// To compare a plane before and after an operation, export it with
+    /// [`contents_grid`][NcPlane#method.contents_grid] before and after,
+    /// and use [`NcGrid::diff`][NcGrid#method.diff].
+    ///
+    /// *(No equivalent C style function)*
+    pub fn diff(&mut self, new: &mut NcPlane) -> NcResult<NcGridDiff> {
+        let old = self.contents_grid(Some(0), Some(0), None, None)?;
+        let new = new.contents_grid(Some(0), Some(0), None, None)?;
+        Ok(old.diff(&new))
+    }
+}
+
+/// Returns true if both cells have the same glyph, width, styles and channels.
+fn same_cell(a: &NcGridCell, b: &NcGridCell) -> bool {
+    let glyph =
+        |c: &NcGridCell| if c.egc.is_empty() && c.width != 0 { " " } else { c.egc.as_str() };
+    glyph(a) == glyph(b) && a.width == b.width && a.style == b.style && a.channels == b.channels
+}
+
+mod std_impls {
+    use super::{NcGridChange, NcGridDiff};
+    use crate::grid::golden::describe;
+    use core::{fmt, slice::Iter};
+
+    impl fmt::Display for NcGridChange {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "{}:{} {} -> {}",
+                self.y,
+                self.x,
+                describe(&self.old),
+                describe(&self.new)
+            )
+        }
+    }
+
+    /// Lists the cells that differ, one per line.
+    impl fmt::Display for NcGridDiff {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            if self.old_dim != self.new_dim {
+                writeln!(
+                    f,
+                    "{}x{} -> {}x{}",
+                    self.old_dim.0, self.old_dim.1, self.new_dim.0, self.new_dim.1
+                )?;
+            }
+            for change in &self.changes {
+                writeln!(f, "{}", change)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl<'a> IntoIterator for &'a NcGridDiff {
+        type Item = &'a NcGridChange;
+        type IntoIter = Iter<'a, NcGridChange>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            self.iter()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::same_cell;
+    use crate::{NcAlpha, NcChannels, NcGrid, NcGridCell, NcStyle};
+
+    #[test]
+    fn diff_cells() {
+        let old = NcGrid::from_escapes(b"abc\r\ndef", 2, 3);
+        let new = NcGrid::from_escapes(b"abc\r\nd\x1b[1mef", 2, 3);
+        assert![old.diff(&old).is_empty()];
+
+        let diff = old.diff(&new);
+        let changed: Vec<_> = diff.iter().map(|c| (c.y, c.x)).collect();
+        assert_eq![vec![(1, 1), (1, 2)], changed];
+        assert_eq![NcStyle::None, diff.changes()[0].old.style];
+        assert_eq![NcStyle::Bold, diff.changes()[0].new.style];
+    }
+
+    #[test]
+    fn diff_dimensions() {
+        let old = NcGrid::from_escapes(b"ab", 1, 2);
+        let new = NcGrid::from_escapes(b"ab\r\nc", 2, 3);
+        let diff = old.diff(&new);
+        assert![!diff.is_empty()];
+        assert_eq![(2, 3), diff.dim_yx()];
+        // the blank cells beyond the old grid are the same as the new blanks
+        assert_eq![1, diff.len()];
+        assert_eq!["c", diff.changes()[0].new.egc];
+        assert_eq![
+            "1:0 \" \" style=none fg=default bg=default -> \"c\" style=none fg=default bg=default",
+            diff.changes()[0].to_string()
+        ];
+    }
+
+    #[test]
+    fn diff_alpha() {
+        let opaque = NcGridCell::new("a", NcStyle::None, NcChannels::with_default(), 1);
+        let mut blended = opaque.clone();
+        blended.channels.set_bg_alpha(NcAlpha::Blend).unwrap();
+        assert![same_cell(&opaque, &opaque.clone())];
+        assert![!same_cell(&opaque, &blended)];
+    }
+}
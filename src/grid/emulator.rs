@@ -0,0 +1,445 @@
+//! A minimal terminal emulator, producing an `NcGrid`.
+
+use crate::{
+    grid::{NcGrid, NcGridCell},
+    unicode::{char_width, GraphemeBreaker},
+    NcChannel, NcChannels, NcStyle,
+};
+
+/// The state of the escape sequence parser.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    /// Printing text.
+    Ground,
+    /// After an `ESC`.
+    Escape,
+    /// After an `ESC` introducing a charset designation.
+    Charset,
+    /// Inside a Control Sequence.
+    Csi,
+    /// Inside an Operating System Command.
+    Osc,
+    /// Inside a string (DCS, APC, PM or SOS), e.g. graphics.
+    String,
+    /// After an `ESC` inside an OSC or a string, expecting the terminator.
+    StringEscape,
+}
+
+/// Interprets the escape sequences written to a terminal,
+/// keeping track of its contents in an [`NcGrid`].
+///
+/// Only what notcurses uses to draw text is interpreted: cursor movements,
+/// erasing, text, styles and colors.
+#[derive(Debug)]
+pub(crate) struct Emulator {
+    grid: NcGrid,
+    y: u32,
+    x: u32,
+    style: NcStyle,
+    channels: NcChannels,
+    state: State,
+    /// The parameters and intermediate bytes of the current sequence.
+    params: Vec<u8>,
+    /// The bytes of an incomplete UTF-8 character.
+    utf8: Vec<u8>,
+    /// The coordinates of the last printed glyph.
+    last: Option<(u32, u32)>,
+    /// The grapheme cluster boundaries of the printed characters.
+    breaker: GraphemeBreaker,
+}
+
+impl Emulator {
+    /// New emulator of an empty terminal of `rows` × `cols`.
+    pub(crate) fn new(rows: u32, cols: u32) -> Self {
+        Self {
+            grid: NcGrid::new(rows, cols),
+            y: 0,
+            x: 0,
+            style: NcStyle::None,
+            channels: NcChannels::with_default(),
+            state: State::Ground,
+            params: Vec::new(),
+            utf8: Vec::new(),
+            last: None,
+            breaker: GraphemeBreaker::default(),
+        }
+    }
+
+    /// Returns the current contents of the terminal.
+    pub(crate) fn grid(&self) -> &NcGrid {
+        &self.grid
+    }
+
+    /// Returns the contents of the terminal, consuming the emulator.
+    pub(crate) fn into_grid(self) -> NcGrid {
+        self.grid
+    }
+
+    /// Resizes the terminal to `rows` × `cols`, keeping the cells that fit.
+    pub(crate) fn resize(&mut self, rows: u32, cols: u32) {
+        let mut grid = NcGrid::new(rows, cols);
+        for y in 0..rows {
+            for x in 0..cols {
+                if let (Some(new), Some(old)) = (grid.get_mut(y, x), self.grid.get(y, x)) {
+                    *new = old.clone();
+                }
+            }
+        }
+        self.grid = grid;
+        self.move_to(self.y, self.x);
+        self.last = None;
+        self.breaker.reset();
+    }
+
+    /// Interprets the `bytes` written to the terminal.
+    ///
+    /// Sequences can be split among several calls.
+    pub(crate) fn feed(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.byte(b);
+        }
+    }
+
+    fn byte(&mut self, b: u8) {
+        match self.state {
+            State::Ground => match b {
+                0x1b => {
+                    self.utf8.clear();
+                    self.state = State::Escape;
+                }
+                b'\r' => self.x = 0,
+                b'\n' => self.move_to(self.y.saturating_add(1), self.x),
+                0x08 => self.x = self.x.saturating_sub(1),
+                0..=0x1f | 0x7f => (),
+                _ => self.text_byte(b),
+            },
+            State::Escape => {
+                self.state = match b {
+                    b'[' => {
+                        self.params.clear();
+                        State::Csi
+                    }
+                    b']' => State::Osc,
+                    b'P' | b'_' | b'^' | b'X' => State::String,
+                    b'(' | b')' | b'*' | b'+' => State::Charset,
+                    _ => State::Ground,
+                }
+            }
+            State::Charset => self.state = State::Ground,
+            State::Csi => match b {
+                0x20..=0x3f => self.params.push(b),
+                0x40..=0x7e => {
+                    self.state = State::Ground;
+                    self.csi(b);
+                }
+                _ => self.state = State::Ground,
+            },
+            State::Osc => match b {
+                0x07 => self.state = State::Ground,
+                0x1b => self.state = State::StringEscape,
+                _ => (),
+            },
+            State::String => {
+                if b == 0x1b {
+                    self.state = State::StringEscape;
+                }
+            }
+            State::StringEscape => {
+                self.state = if b == b'\\' { State::Ground } else { State::String };
+            }
+        }
+    }
+
+    /// Accumulates a byte of UTF-8 text, printing the complete characters.
+    fn text_byte(&mut self, b: u8) {
+        self.utf8.push(b);
+        match core::str::from_utf8(&self.utf8) {
+            Ok(s) => {
+                let c = s.chars().next().unwrap_or(char::REPLACEMENT_CHARACTER);
+                self.utf8.clear();
+                self.print(c);
+            }
+            Err(e) if e.error_len().is_some() || self.utf8.len() >= 4 => {
+                self.utf8.clear();
+                self.print(char::REPLACEMENT_CHARACTER);
+            }
+            Err(_) => (),
+        }
+    }
+
+    /// Prints a character at the cursor.
+    fn print(&mut self, c: char) {
+        let width = char_width(c);
+        // characters continuing a grapheme cluster, or that occupy no
+        // columns, are part of the last printed glyph
+        if self.breaker.joins(c) || width == 0 {
+            if let Some(cell) = self.last.and_then(|(y, x)| self.grid.get_mut(y, x)) {
+                cell.egc.push(c);
+            }
+            return;
+        }
+
+        let (rows, cols) = self.grid.dim_yx();
+        if self.x + width as u32 > cols {
+            // autowrap
+            self.x = 0;
+            self.y += 1;
+        }
+        if self.y >= rows {
+            self.y = rows.saturating_sub(1);
+            return;
+        }
+        let (y, x) = (self.y, self.x);
+
+        // don't leave halves of overwritten wide glyphs behind
+        let old_width = self.grid.get(y, x).map_or(1, |c| c.width);
+        if old_width == 0 && x > 0 {
+            self.blank(y, x - 1);
+        }
+        if old_width == 2 && width == 1 {
+            self.blank(y, x + 1);
+        }
+
+        let mut egc = String::new();
+        egc.push(c);
+        if let Some(cell) = self.grid.get_mut(y, x) {
+            *cell = NcGridCell { egc, style: self.style, channels: self.channels, width };
+        }
+        if width == 2 {
+            if let Some(cell) = self.grid.get_mut(y, x + 1) {
+                *cell = NcGridCell::new("", self.style, self.channels, 0);
+            }
+        }
+        self.last = Some((y, x));
+        self.x += width as u32;
+    }
+
+    fn blank(&mut self, y: u32, x: u32) {
+        if let Some(cell) = self.grid.get_mut(y, x) {
+            *cell = NcGridCell::blank();
+        }
+    }
+
+    /// Moves the cursor, keeping it inside the terminal.
+    fn move_to(&mut self, y: u32, x: u32) {
+        let (rows, cols) = self.grid.dim_yx();
+        self.y = y.min(rows.saturating_sub(1));
+        self.x = x.min(cols.saturating_sub(1));
+    }
+
+    /// Returns the numeric parameters of the current sequence.
+    ///
+    /// Missing parameters are returned as `None`, and subparameters
+    /// (separated by `:`) are returned together.
+    fn numbers(&self) -> Vec<Vec<Option<u32>>> {
+        self.params
+            .split(|&b| b == b';')
+            .map(|param| {
+                param
+                    .split(|&b| b == b':')
+                    .map(|sub| core::str::from_utf8(sub).ok().and_then(|s| s.parse().ok()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Interprets a Control Sequence ending in `fin`.
+    fn csi(&mut self, fin: u8) {
+        // private sequences, like setting terminal modes, are ignored
+        if self.params.first().map_or(false, |b| b"?<=>".contains(b))
+            || self.params.iter().any(|b| (0x20..=0x2f).contains(b))
+        {
+            return;
+        }
+        let numbers = self.numbers();
+        let arg = |i: usize, default: u32| {
+            numbers
+                .get(i)
+                .and_then(|p| p[0])
+                .filter(|&n| n != 0)
+                .unwrap_or(default)
+        };
+        let (y, x) = (self.y, self.x);
+        let cols = self.grid.cols();
+        // the linear position of a cell in the row `y`
+        let at = |y: u32, x: u32| y.saturating_mul(cols).saturating_add(x);
+        match fin {
+            b'H' | b'f' => self.move_to(arg(0, 1) - 1, arg(1, 1) - 1),
+            b'A' => self.move_to(y.saturating_sub(arg(0, 1)), x),
+            b'B' => self.move_to(y.saturating_add(arg(0, 1)), x),
+            b'C' => self.move_to(y, x.saturating_add(arg(0, 1))),
+            b'D' => self.move_to(y, x.saturating_sub(arg(0, 1))),
+            b'E' => self.move_to(y.saturating_add(arg(0, 1)), 0),
+            b'F' => self.move_to(y.saturating_sub(arg(0, 1)), 0),
+            b'G' | b'`' => self.move_to(y, arg(0, 1) - 1),
+            b'd' => self.move_to(arg(0, 1) - 1, x),
+            b'J' => {
+                let rows = self.grid.rows();
+                let (from, to) = match numbers[0][0].unwrap_or(0) {
+                    0 => (at(y, x), at(rows, 0)),
+                    1 => (0, at(y, x).saturating_add(1)),
+                    _ => (0, at(rows, 0)),
+                };
+                self.erase(from, to);
+            }
+            b'K' => {
+                let (from, to) = match numbers[0][0].unwrap_or(0) {
+                    0 => (x, cols),
+                    1 => (0, x.saturating_add(1)),
+                    _ => (0, cols),
+                };
+                self.erase(at(y, from), at(y, to.min(cols)));
+            }
+            b'X' => self.erase(at(y, x), at(y, x.saturating_add(arg(0, 1)).min(cols))),
+            b'm' => self.sgr(&numbers),
+            _ => (),
+        }
+    }
+
+    /// Erases the cells between the linear positions `from` and `to`.
+    fn erase(&mut self, from: u32, to: u32) {
+        let cols = self.grid.cols().max(1);
+        for i in from..to {
+            self.blank(i / cols, i % cols);
+        }
+    }
+
+    /// Interprets a Select Graphic Rendition sequence.
+    fn sgr(&mut self, numbers: &[Vec<Option<u32>>]) {
+        let mut i = 0;
+        while i < numbers.len() {
+            let param = &numbers[i];
+            i += 1;
+            match param[0].unwrap_or(0) {
+                0 => {
+                    self.style = NcStyle::None;
+                    self.channels = NcChannels::with_default();
+                }
+                1 => self.style |= NcStyle::Bold,
+                3 => self.style |= NcStyle::Italic,
+                4 => {
+                    self.style &= !(NcStyle::Underline | NcStyle::Undercurl);
+                    match param.get(1).copied().flatten() {
+                        Some(0) => (),
+                        Some(3) => self.style |= NcStyle::Undercurl,
+                        _ => self.style |= NcStyle::Underline,
+                    }
+                }
+                9 => self.style |= NcStyle::Struck,
+                22 => self.style &= !NcStyle::Bold,
+                23 => self.style &= !NcStyle::Italic,
+                24 => self.style &= !(NcStyle::Underline | NcStyle::Undercurl),
+                29 => self.style &= !NcStyle::Struck,
+                n @ 30..=37 => {
+                    self.channels.set_fg_palindex((n - 30) as u8);
+                }
+                n @ 90..=97 => {
+                    self.channels.set_fg_palindex((n - 90 + 8) as u8);
+                }
+                n @ 40..=47 => {
+                    self.channels.set_bg_palindex((n - 40) as u8);
+                }
+                n @ 100..=107 => {
+                    self.channels.set_bg_palindex((n - 100 + 8) as u8);
+                }
+                // the whole channel is reset, so that its previous color
+                // doesn't make it differ from a default one
+                39 => {
+                    self.channels.set_fchannel(NcChannel::with_default());
+                }
+                49 => {
+                    self.channels.set_bchannel(NcChannel::with_default());
+                }
+                n @ (38 | 48) => {
+                    // either 38:2:r:g:b / 38:2::r:g:b / 38:5:i, or separated by ';'
+                    let args: Vec<u32> = if param.len() > 1 {
+                        param[1..].iter().map(|p| p.unwrap_or(0)).collect()
+                    } else {
+                        let rest: Vec<u32> =
+                            numbers[i..].iter().map(|p| p[0].unwrap_or(0)).collect();
+                        let len = match rest.first() {
+                            Some(2) => 4,
+                            Some(5) => 2,
+                            _ => 1,
+                        };
+                        i += len.min(rest.len());
+                        rest.into_iter().take(len).collect()
+                    };
+                    self.extended_color(n == 38, &args);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Sets an extended foreground or background color.
+    fn extended_color(&mut self, fg: bool, args: &[u32]) {
+        match args {
+            [5, index, ..] => {
+                if fg {
+                    self.channels.set_fg_palindex(*index as u8);
+                } else {
+                    self.channels.set_bg_palindex(*index as u8);
+                }
+            }
+            [2, rgb @ ..] if rgb.len() >= 3 => {
+                // skip the optional colorspace identifier
+                let rgb = &rgb[rgb.len() - 3..];
+                let (r, g, b) = (rgb[0] as u8, rgb[1] as u8, rgb[2] as u8);
+                if fg {
+                    self.channels.set_fg_rgb8(r, g, b);
+                } else {
+                    self.channels.set_bg_rgb8(r, g, b);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Emulator;
+    use crate::{NcRgb, NcStyle};
+
+    #[test]
+    fn text_and_movements() {
+        let mut e = Emulator::new(3, 6);
+        e.feed(b"\x1b[?25l\x1b[2;3Hab\x1b[1;1Hx\r\ny\x1b]0;title\x07z");
+        let grid = e.grid();
+        assert_eq!["x     \nyzab  \n      ", grid.text()];
+        e.feed(b"\x1b[2;1H\x1b[K");
+        assert_eq![Some("      ".to_owned()), e.grid().row_text(1)];
+    }
+
+    #[test]
+    fn styles_and_colors() {
+        let mut e = Emulator::new(1, 4);
+        e.feed(b"\x1b[1;38;2;255;0;0ma\x1b[22;48;5;4mb\x1b[4:3m\x1b[39mc\x1b[0md");
+        let grid = e.into_grid();
+
+        let a = grid.get(0, 0).unwrap();
+        assert_eq![NcStyle::Bold, a.style];
+        assert_eq![NcRgb(0xff0000), a.channels.fg_rgb()];
+        assert![a.channels.bg_default_p()];
+
+        let b = grid.get(0, 1).unwrap();
+        assert_eq![NcStyle::None, b.style];
+        assert_eq![4, b.channels.bg_palindex()];
+
+        let c = grid.get(0, 2).unwrap();
+        assert_eq![NcStyle::Undercurl, c.style];
+        assert![c.channels.fg_default_p()];
+
+        assert![grid.get(0, 3).unwrap().has_default_attrs()];
+    }
+
+    #[test]
+    fn huge_parameters() {
+        let mut e = Emulator::new(2, 4);
+        e.feed(b"a\x1b[4294967295Cb\x1b[4294967295Bc\x1b[4294967295X\x1b[4294967295E");
+        assert_eq!["a  b\n   c", e.grid().text()];
+        e.feed(b"\x1b[4294967295;4294967295H\x1b[1J");
+        assert_eq!["    \n    ", e.into_grid().text()];
+    }
+}
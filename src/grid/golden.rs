@@ -0,0 +1,412 @@
+//! Golden files of `NcGrid`s.
+
+use std::{env, fmt::Write as _, fs, path::Path};
+
+use crate::{
+    grid::{NcGrid, NcGridCell},
+    unicode::{char_width, GraphemeBreaker},
+    NcAlpha, NcChannels, NcError, NcErrorKind, NcResult, NcRgb, NcStyle,
+};
+
+/// The environment variable that makes
+/// [`check_golden`][NcGrid#method.check_golden] write the golden files.
+const NC_UPDATE_GOLDEN: &str = "NC_UPDATE_GOLDEN";
+
+/// The first line of a golden file, before the dimensions.
+const HEADER: &str = "# libnotcurses-sys grid";
+
+/// The line separating the glyphs from the attributes.
+const ATTRIBUTES: &str = "# attributes";
+
+/// The maximum number of differing cells listed in a report.
+const MAX_REPORTED: usize = 20;
+
+/// The names of the styles, in the order they are written.
+const STYLES: [(NcStyle, &str); 5] = [
+    (NcStyle::Bold, "bold"),
+    (NcStyle::Italic, "italic"),
+    (NcStyle::Underline, "underline"),
+    (NcStyle::Undercurl, "undercurl"),
+    (NcStyle::Struck, "struck"),
+];
+
+/// The names of the non-opaque alphas, which are written only when set.
+const ALPHAS: [(NcAlpha, &str); 3] = [
+    (NcAlpha::Blend, "blend"),
+    (NcAlpha::HighContrast, "highcontrast"),
+    (NcAlpha::Transparent, "transparent"),
+];
+
+/// # Golden files
+impl NcGrid {
+    /// Returns the human-readable golden representation of the grid.
+    ///
+    /// It starts with the glyphs of each row between `|`, followed by
+    /// the styles, colors and alphas of the cells that don't have the default
+    /// ones, as runs of cells with the same attributes:
+    ///
+    /// ```txt
+    /// # libnotcurses-sys grid 2x12
+    /// |Error: oops |
+    /// |            |
+    /// # attributes
+    /// 0:0-5 style=bold fg=#ff0000 bg=default
+    /// ```
+    ///
+    /// The alphas are written as `fg-alpha=…` and `bg-alpha=…` fields,
+    /// only when they aren't opaque.
+    pub fn to_golden(&self) -> String {
+        let mut golden = format!["{} {}x{}\n", HEADER, self.rows, self.cols];
+        for y in 0..self.rows {
+            let _ = writeln!(golden, "|{}|", self.row_text(y).unwrap_or_default());
+        }
+        golden.push_str(ATTRIBUTES);
+        golden.push('\n');
+        for y in 0..self.rows {
+            let mut x = 0;
+            while x < self.cols {
+                let attrs = self.attrs(y, x);
+                let start = x;
+                while x + 1 < self.cols && self.attrs(y, x + 1) == attrs {
+                    x += 1;
+                }
+                if let Some(attrs) = attrs {
+                    if start == x {
+                        let _ = writeln!(golden, "{}:{} {}", y, start, attrs);
+                    } else {
+                        let _ = writeln!(golden, "{}:{}-{} {}", y, start, x, attrs);
+                    }
+                }
+                x += 1;
+            }
+        }
+        golden
+    }
+
+    /// Parses a grid from its golden representation.
+    ///
+    /// See [`to_golden`][NcGrid#method.to_golden].
+    pub fn from_golden(golden: &str) -> NcResult<Self> {
+        let invalid = |n: usize| {
+            NcError::with_kind(
+                NcErrorKind::InvalidArgument,
+                &format!["NcGrid.from_golden(): invalid line {}", n + 1],
+            )
+        };
+        let mut lines = golden.lines().enumerate();
+
+        let (rows, cols) = lines
+            .next()
+            .and_then(|(_, l)| l.strip_prefix(HEADER))
+            .and_then(|dims| {
+                let (rows, cols) = dims.trim().split_once('x')?;
+                Some((rows.parse().ok()?, cols.parse().ok()?))
+            })
+            .ok_or_else(|| invalid(0))?;
+        let mut grid = NcGrid::new(rows, cols);
+
+        for y in 0..rows {
+            let (n, line) = lines.next().ok_or_else(|| invalid(y as usize + 1))?;
+            let text = line
+                .strip_prefix('|')
+                .and_then(|l| l.strip_suffix('|'))
+                .ok_or_else(|| invalid(n))?;
+            grid.set_row_text(y, text);
+        }
+
+        match lines.next() {
+            Some((_, ATTRIBUTES)) => (),
+            Some((n, _)) => return Err(invalid(n)),
+            None => return Ok(grid),
+        }
+        for (n, line) in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (y, from, to, style, channels) = parse_attrs(line).ok_or_else(|| invalid(n))?;
+            for x in from..=to {
+                let cell = grid.get_mut(y, x).ok_or_else(|| invalid(n))?;
+                cell.style = style;
+                cell.channels = channels;
+            }
+        }
+        Ok(grid)
+    }
+
+    /// Compares the grid against the golden file at `path`, returning an
+    /// error with a report of the differing cells if they don't match.
+    ///
+    /// If the `NC_UPDATE_GOLDEN` environment variable is set, the golden
+    /// file is written instead, creating its directory if needed.
+    /// It's an error if the golden file doesn't exist otherwise.
+    ///
+    /// Only the glyphs, the styles, the colors and the alphas are compared.
+    pub fn check_golden<P: AsRef<Path>>(&self, path: P) -> NcResult<()> {
+        let path = path.as_ref();
+        if env::var_os(NC_UPDATE_GOLDEN).is_some() {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(path, self.to_golden())?;
+            return Ok(());
+        }
+        if !path.exists() {
+            return Err(NcError::with_kind(
+                NcErrorKind::Io,
+                &format![
+                    "the golden file {} doesn't exist; set {}=1 to write it",
+                    path.display(),
+                    NC_UPDATE_GOLDEN
+                ],
+            ));
+        }
+        let golden = NcGrid::from_golden(&fs::read_to_string(path)?)?;
+        match golden.report(self) {
+            None => Ok(()),
+            Some(report) => Err(NcError::with_kind(
+                NcErrorKind::Other,
+                &format!["the frame differs from {}: {}", path.display(), report],
+            )),
+        }
+    }
+
+    /// Like [`check_golden`][NcGrid#method.check_golden], but panics with
+    /// the report if the grid doesn't match the golden file.
+    pub fn assert_golden<P: AsRef<Path>>(&self, path: P) {
+        if let Err(e) = self.check_golden(path) {
+            panic!["{}", e];
+        }
+    }
+
+    /// Returns a report of the differences with the `found` grid,
+    /// or `None` if they match.
+    fn report(&self, found: &NcGrid) -> Option<String> {
+        let mut report = String::new();
+        if self.dim_yx() != found.dim_yx() {
+            let _ = write!(
+                report,
+                "expected {}x{} cells, found {}x{}",
+                self.rows, self.cols, found.rows, found.cols
+            );
+        } else {
+            let diff = self.diff(found);
+            if diff.is_empty() {
+                return None;
+            }
+            let _ = writeln!(report, "{} differing cells", diff.len());
+            for change in diff.iter().take(MAX_REPORTED) {
+                let (y, x) = (change.y, change.x);
+                let _ = writeln!(
+                    report,
+                    "  {}:{} expected {}\n  {}:{} found    {}",
+                    y,
+                    x,
+                    describe(&change.old),
+                    y,
+                    x,
+                    describe(&change.new),
+                );
+            }
+            if diff.len() > MAX_REPORTED {
+                let _ = writeln!(report, "  … and {} more", diff.len() - MAX_REPORTED);
+            }
+        }
+        let _ = write!(
+            report,
+            "\nexpected:\n{}found:\n{}",
+            self.to_golden(),
+            found.to_golden()
+        );
+        Some(report)
+    }
+
+    /// Returns the golden attributes of the cell at `y`, `x`,
+    /// or `None` if they are the default ones.
+    fn attrs(&self, y: u32, x: u32) -> Option<String> {
+        self.get(y, x)
+            .filter(|c| !c.has_default_attrs())
+            .map(|c| format_attrs(c.style, c.channels))
+    }
+
+    /// Sets the glyphs of the row `y` from `text`, leaving the rest blank.
+    fn set_row_text(&mut self, y: u32, text: &str) {
+        let mut x = 0;
+        let mut breaker = GraphemeBreaker::default();
+        for c in text.chars() {
+            let width = char_width(c);
+            if (breaker.joins(c) || width == 0) && x > 0 {
+                let last = if self.get(y, x - 1).map_or(false, |c| c.wide_right_p()) {
+                    x - 2
+                } else {
+                    x - 1
+                };
+                if let Some(cell) = self.get_mut(y, last) {
+                    cell.egc.push(c);
+                }
+                continue;
+            }
+            if let Some(cell) = self.get_mut(y, x) {
+                cell.egc = c.to_string();
+                cell.width = width;
+            }
+            if width == 2 {
+                if let Some(cell) = self.get_mut(y, x + 1) {
+                    *cell = NcGridCell::new("", NcStyle::None, NcChannels::with_default(), 0);
+                }
+            }
+            x += width as u32;
+        }
+    }
+}
+
+/// Returns a description of a cell for a report.
+pub(super) fn describe(cell: &NcGridCell) -> String {
+    format!["{:?} {}", cell.egc, format_attrs(cell.style, cell.channels)]
+}
+
+/// Formats the styles and colors of a cell.
+fn format_attrs(style: NcStyle, channels: NcChannels) -> String {
+    let styles: Vec<&str> = STYLES
+        .iter()
+        .filter(|(s, _)| style.has(*s))
+        .map(|(_, name)| *name)
+        .collect();
+    let style = if styles.is_empty() { "none".to_owned() } else { styles.join("+") };
+    let fg = if channels.fg_default_p() {
+        "default".to_owned()
+    } else if channels.fg_palindex_p() {
+        format!["palette:{}", channels.fg_palindex()]
+    } else {
+        format!["#{:06x}", channels.fg_rgb().0]
+    };
+    let bg = if channels.bg_default_p() {
+        "default".to_owned()
+    } else if channels.bg_palindex_p() {
+        format!["palette:{}", channels.bg_palindex()]
+    } else {
+        format!["#{:06x}", channels.bg_rgb().0]
+    };
+    let mut attrs = format!["style={} fg={} bg={}", style, fg, bg];
+    for (key, alpha) in [("fg", channels.fg_alpha()), ("bg", channels.bg_alpha())] {
+        if let Some((_, name)) = ALPHAS.iter().find(|(a, _)| *a == alpha) {
+            let _ = write!(attrs, " {}-alpha={}", key, name);
+        }
+    }
+    attrs
+}
+
+/// Parses a line of attributes: `y:from-to style=… fg=… bg=…`,
+/// optionally followed by `fg-alpha=…` and `bg-alpha=…`.
+fn parse_attrs(line: &str) -> Option<(u32, u32, u32, NcStyle, NcChannels)> {
+    let mut fields = line.split_whitespace();
+    let (y, xs) = fields.next()?.split_once(':')?;
+    let (from, to) = match xs.split_once('-') {
+        Some((from, to)) => (from.parse().ok()?, to.parse().ok()?),
+        None => (xs.parse().ok()?, xs.parse().ok()?),
+    };
+
+    let mut style = NcStyle::None;
+    let mut channels = NcChannels::with_default();
+    for field in fields {
+        let (key, value) = field.split_once('=')?;
+        match key {
+            "style" => {
+                for name in value.split('+').filter(|&n| n != "none") {
+                    style |= STYLES.iter().find(|(_, n)| *n == name)?.0;
+                }
+            }
+            "fg" | "bg" => {
+                let fg = key == "fg";
+                if let Some(index) = value.strip_prefix("palette:") {
+                    let index = index.parse().ok()?;
+                    if fg {
+                        channels.set_fg_palindex(index);
+                    } else {
+                        channels.set_bg_palindex(index);
+                    }
+                } else if let Some(hex) = value.strip_prefix('#') {
+                    let rgb = u32::from_str_radix(hex, 16).ok()?;
+                    if fg {
+                        channels.set_fg_rgb(NcRgb(rgb));
+                    } else {
+                        channels.set_bg_rgb(NcRgb(rgb));
+                    }
+                } else if value != "default" {
+                    return None;
+                }
+            }
+            "fg-alpha" | "bg-alpha" => {
+                let alpha = ALPHAS.iter().find(|(_, n)| *n == value)?.0;
+                if key == "fg-alpha" {
+                    channels.set_fg_alpha(alpha).ok()?;
+                } else {
+                    channels.set_bg_alpha(alpha).ok()?;
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some((y.parse().ok()?, from, to, style, channels))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{grid::NcGrid, NcAlpha, NcStyle};
+
+    fn frame() -> NcGrid {
+        NcGrid::from_escapes(
+            b"\x1b[1;38;2;255;0;0mError:\x1b[0m oops\r\n\x1b[44mok",
+            2,
+            12,
+        )
+    }
+
+    #[test]
+    fn golden_roundtrip() {
+        let grid = frame();
+        let golden = grid.to_golden();
+        assert_eq![
+            "# libnotcurses-sys grid 2x12
+|Error: oops |
+|ok          |
+# attributes
+0:0-5 style=bold fg=#ff0000 bg=default
+1:0-1 style=none fg=default bg=palette:4
+",
+            golden
+        ];
+        let parsed = NcGrid::from_golden(&golden).unwrap();
+        assert_eq![None, parsed.report(&grid)];
+    }
+
+    #[test]
+    fn golden_report() {
+        let golden = frame();
+        let mut found = frame();
+        found.get_mut(0, 7).unwrap().egc = "O".into();
+        found.get_mut(0, 0).unwrap().style = NcStyle::Italic;
+
+        let report = golden.report(&found).unwrap();
+        assert![report.starts_with("2 differing cells\n")];
+        assert![report.contains("0:7 expected \"o\" style=none")];
+        assert![report.contains("0:0 found    \"E\" style=italic")];
+        assert![NcGrid::from_golden("# something else").is_err()];
+    }
+
+    #[test]
+    fn golden_alpha() {
+        let golden = frame();
+        let mut found = frame();
+        let cell = found.get_mut(1, 0).unwrap();
+        cell.channels.set_bg_alpha(NcAlpha::Blend).unwrap();
+
+        let report = golden.report(&found).unwrap();
+        assert![report.starts_with("1 differing cells\n")];
+        assert![
+            report.contains("1:0 found    \"o\" style=none fg=default bg=palette:4 bg-alpha=blend")
+        ];
+
+        let parsed = NcGrid::from_golden(&found.to_golden()).unwrap();
+        assert_eq![None, parsed.report(&found)];
+    }
+}
@@ -0,0 +1,227 @@
+//! `NcGrid`
+
+use crate::{NcAlpha, NcChannels, NcStyle};
+
+mod diff;
+mod emulator;
+mod golden;
+
+pub use diff::{NcGridChange, NcGridDiff};
+pub(crate) use emulator::Emulator;
+
+/// An owned cell of an [`NcGrid`].
+///
+/// Unlike [`NcCell`][crate::NcCell], it owns its glyph, so it doesn't depend
+/// on the plane it came from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NcGridCell {
+    /// The extended grapheme cluster.
+    ///
+    /// It's empty for the right half of a wide glyph, and for a plane cell
+    /// that has not been drawn.
+    pub egc: String,
+
+    /// The styles.
+    pub style: NcStyle,
+
+    /// The foreground and background channels.
+    pub channels: NcChannels,
+
+    /// The number of columns occupied by the glyph.
+    ///
+    /// It's 2 for the left half of a wide glyph, and 0 for its right half.
+    pub width: u8,
+}
+
+/// # Constructors
+impl NcGridCell {
+    /// New cell with an `egc` of the provided `width`, a `style`
+    /// and `channels`.
+    pub fn new(egc: &str, style: NcStyle, channels: NcChannels, width: u8) -> Self {
+        Self { egc: egc.to_owned(), style, channels, width }
+    }
+
+    /// New blank cell: a space, without styles and with the default colors.
+    pub fn blank() -> Self {
+        Self::new(" ", NcStyle::None, NcChannels::with_default(), 1)
+    }
+}
+
+/// # Methods
+impl NcGridCell {
+    /// Returns true if it's the left half of a wide glyph.
+    pub fn wide_left_p(&self) -> bool {
+        self.width > 1
+    }
+
+    /// Returns true if it's the right half of a wide glyph.
+    pub fn wide_right_p(&self) -> bool {
+        self.width == 0
+    }
+
+    /// Returns true if it has no glyph, no styles, and the default colors
+    /// and alphas.
+    pub fn is_blank(&self) -> bool {
+        (self.egc.is_empty() || self.egc == " ") && self.width != 0 && self.has_default_attrs()
+    }
+
+    /// Returns true if it has no styles, and the default colors and alphas.
+    pub fn has_default_attrs(&self) -> bool {
+        self.style == NcStyle::None
+            && self.channels.fg_default_p()
+            && self.channels.bg_default_p()
+            && self.channels.fg_alpha() == NcAlpha::Opaque
+            && self.channels.bg_alpha() == NcAlpha::Opaque
+    }
+}
+
+mod std_impls {
+    use super::NcGridCell;
+
+    impl Default for NcGridCell {
+        fn default() -> Self {
+            Self::blank()
+        }
+    }
+}
+
+/// A rectangular grid of owned [`NcGridCell`]s.
+///
+/// It can be obtained by interpreting the escape sequences of a rendered
+/// frame, with [`from_escapes`][NcGrid#method.from_escapes] or
+/// [`NcPlane::render_to_grid`][crate::NcPlane#method.render_to_grid],
+/// by exporting a region of a plane, with
+/// [`NcPlane::contents_grid`][crate::NcPlane#method.contents_grid],
+/// and compared against a golden file with
+/// [`check_golden`][NcGrid#method.check_golden].
+#[derive(Clone, Debug, PartialEq)]
+pub struct NcGrid {
+    rows: u32,
+    cols: u32,
+    cells: Vec<NcGridCell>,
+}
+
+/// # Constructors
+impl NcGrid {
+    /// New grid of `rows` × `cols` blank cells.
+    pub fn new(rows: u32, cols: u32) -> Self {
+        Self { rows, cols, cells: vec![NcGridCell::blank(); rows as usize * cols as usize] }
+    }
+
+    /// New grid of `rows` × `cols`, with the result of writing the escape
+    /// sequences in `bytes` to an empty terminal of that size.
+    ///
+    /// The cursor movements, text, styles and colors are interpreted.
+    /// Other sequences, like graphics protocols, are ignored.
+    pub fn from_escapes(bytes: &[u8], rows: u32, cols: u32) -> Self {
+        let mut emulator = Emulator::new(rows, cols);
+        emulator.feed(bytes);
+        emulator.into_grid()
+    }
+}
+
+/// # Methods
+impl NcGrid {
+    /// Returns the number of rows.
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    /// Returns the number of columns.
+    pub fn cols(&self) -> u32 {
+        self.cols
+    }
+
+    /// Returns the number of rows and columns.
+    pub fn dim_yx(&self) -> (u32, u32) {
+        (self.rows, self.cols)
+    }
+
+    /// Returns the cell at `y`, `x`, or `None` if it's out of bounds.
+    pub fn get(&self, y: u32, x: u32) -> Option<&NcGridCell> {
+        self.index(y, x).map(|i| &self.cells[i])
+    }
+
+    /// Returns the cell at `y`, `x` mutably, or `None` if it's out of bounds.
+    pub fn get_mut(&mut self, y: u32, x: u32) -> Option<&mut NcGridCell> {
+        self.index(y, x).map(move |i| &mut self.cells[i])
+    }
+
+    /// Returns the cells of the row `y`, or `None` if it's out of bounds.
+    pub fn row(&self, y: u32) -> Option<&[NcGridCell]> {
+        if y >= self.rows {
+            return None;
+        }
+        let start = y as usize * self.cols as usize;
+        Some(&self.cells[start..start + self.cols as usize])
+    }
+
+    /// Returns an iterator over the rows, from top to bottom.
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[NcGridCell]> {
+        // `chunks` panics with a size of 0
+        self.cells.chunks(self.cols.max(1) as usize)
+    }
+
+    /// Returns an iterator over the cells, row by row,
+    /// along with their `y`, `x` coordinates.
+    pub fn iter_cells(&self) -> impl Iterator<Item = (u32, u32, &NcGridCell)> {
+        let cols = self.cols;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| (i as u32 / cols, i as u32 % cols, cell))
+    }
+
+    /// Returns the glyphs of the row `y`, or `None` if it's out of bounds.
+    ///
+    /// Empty cells are returned as spaces.
+    pub fn row_text(&self, y: u32) -> Option<String> {
+        self.row(y).map(|row| {
+            row.iter()
+                .filter(|c| !c.wide_right_p())
+                .map(|c| if c.egc.is_empty() { " " } else { c.egc.as_str() })
+                .collect()
+        })
+    }
+
+    /// Returns the glyphs of all the rows, separated by newlines.
+    pub fn text(&self) -> String {
+        (0..self.rows)
+            .filter_map(|y| self.row_text(y))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Returns the cell at `y`, `x`, or a blank one if it's out of bounds.
+    fn cell_at(&self, y: u32, x: u32) -> NcGridCell {
+        self.get(y, x).cloned().unwrap_or_default()
+    }
+
+    fn index(&self, y: u32, x: u32) -> Option<usize> {
+        if y < self.rows && x < self.cols {
+            Some(y as usize * self.cols as usize + x as usize)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::NcGrid;
+
+    #[test]
+    fn rows_and_cells() {
+        let grid = NcGrid::from_escapes("ab\r\nc字".as_bytes(), 2, 3);
+        assert_eq![2, grid.iter_rows().count()];
+        assert![grid.iter_rows().all(|row| row.len() == 3)];
+        assert_eq![Some("字"), grid.row(1).map(|row| row[1].egc.as_str())];
+        assert_eq![None, grid.row(2)];
+
+        let cells: Vec<_> = grid.iter_cells().map(|(y, x, c)| (y, x, c.width)).collect();
+        assert_eq![(0, 0, 1), cells[0]];
+        assert_eq![(1, 1, 2), cells[4]];
+        assert_eq![(1, 2, 0), cells[5]];
+        assert_eq![6, cells.len()];
+    }
+}
@@ -0,0 +1,179 @@
+//! `NcEvent`
+
+use crate::{
+    c_api, input::paste::NcPasteCollector, NcBurstPasteOptions, NcInput, NcInputSource,
+    NcInputType, NcKey, NcKeyMod, NcReceived, NcResult, NcTime,
+};
+
+/// A complete input event, built from an [`NcInput`].
+///
+/// Unlike [`NcReceived`], it keeps the modifiers, the kind of event
+/// (press, repeat or release) and the mouse coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NcEvent {
+    /// A key press, repeat or release.
+    Key {
+        /// The received character or synthesized key.
+        key: NcReceived,
+        /// Whether it's a press, a repeat or a release.
+        kind: NcInputType,
+        /// The active modifiers.
+        modifiers: NcKeyMod,
+    },
+
+    /// A mouse event.
+    Mouse {
+        /// The button, one of [`NcKey::Button1`]…[`NcKey::Button11`],
+        /// or [`NcKey::Motion`] for movement without any button.
+        button: NcKey,
+        /// Whether it's a press, a repeat or a release.
+        kind: NcInputType,
+        /// The row, relative to the terminal's origin.
+        y: i32,
+        /// The column, relative to the terminal's origin.
+        x: i32,
+        /// The pixel offset within the cell row, or -1 if unknown.
+        ypx: i32,
+        /// The pixel offset within the cell column, or -1 if unknown.
+        xpx: i32,
+        /// The active modifiers.
+        modifiers: NcKeyMod,
+    },
+
+    /// The terminal has been resized.
+    Resize,
+
+    /// A pasted string.
+    ///
+    /// Notcurses reports pasted text as a series of key events, so this is
+    /// never built from a single [`NcInput`]. It's only produced by the opt-in
+    /// heuristic enabled with
+    /// [`NcEvents::with_burst_paste`][NcEvents#method.with_burst_paste].
+    Paste(String),
+
+    /// A signal was received (e.g. `SIGCONT`).
+    Signal,
+}
+
+/// # Constructors
+impl NcEvent {
+    /// Returns the event corresponding to an [`NcInput`],
+    /// or `None` if it represents no input.
+    pub fn from_input(input: &NcInput) -> Option<NcEvent> {
+        let received = NcReceived::from(input.id);
+        let kind = NcInputType::from(input.evtype);
+        let modifiers = NcKeyMod(input.modifiers);
+
+        match received {
+            NcReceived::NoInput => None,
+            NcReceived::Event(NcKey::Resize) => Some(NcEvent::Resize),
+            NcReceived::Event(NcKey::Signal) => Some(NcEvent::Signal),
+            NcReceived::Event(key) if c_api::nckey_mouse_p(key.0) => Some(NcEvent::Mouse {
+                button: key,
+                kind,
+                y: input.y,
+                x: input.x,
+                ypx: input.ypx,
+                xpx: input.xpx,
+                modifiers,
+            }),
+            key => Some(NcEvent::Key { key, kind, modifiers }),
+        }
+    }
+}
+
+/// # Methods
+impl NcEvent {
+    /// Returns true if it's a key event for the `key`, regardless of
+    /// the modifiers and the kind of event.
+    pub fn is_key(&self, key: NcReceived) -> bool {
+        matches!(self, NcEvent::Key { key: k, .. } if *k == key)
+    }
+
+    /// Returns true if it's a key or mouse release.
+    pub fn is_release(&self) -> bool {
+        matches!(
+            self,
+            NcEvent::Key { kind: NcInputType::Release, .. }
+                | NcEvent::Mouse { kind: NcInputType::Release, .. }
+        )
+    }
+
+    /// Returns the modifiers of a key or mouse event.
+    pub fn modifiers(&self) -> NcKeyMod {
+        match self {
+            NcEvent::Key { modifiers, .. } | NcEvent::Mouse { modifiers, .. } => *modifiers,
+            _ => NcKeyMod::None,
+        }
+    }
+}
+
+/// An iterator over the input [`NcEvent`]s of an [`NcInputSource`].
+///
+/// Each call to `next` waits for an event up to the timeout,
+/// and the iteration ends when the timeout elapses without input.
+///
+/// It's returned by [`Nc::events`][crate::Nc#method.events]
+/// and [`NcContext::events`][crate::NcContext#method.events], and it can
+/// also be built over any other source, e.g. an
+/// [`NcInputReplayer`][crate::NcInputReplayer].
+#[derive(Debug)]
+pub struct NcEvents<S: NcInputSource> {
+    source: S,
+    timeout: Option<NcTime>,
+    paste: NcPasteCollector,
+}
+
+/// # Constructors
+impl<S: NcInputSource> NcEvents<S> {
+    /// New iterator over the events of `source`, waiting for each one
+    /// up to `timeout`, or indefinitely if it's `None`.
+    pub fn new(source: S, timeout: Option<NcTime>) -> Self {
+        Self { source, timeout, paste: NcPasteCollector::default() }
+    }
+
+    /// Enables the experimental heuristic that collects bursts of characters
+    /// into single [`NcEvent::Paste`] events, according to the `options`.
+    ///
+    /// It's disabled by default. See [`NcBurstPasteOptions`] for details
+    /// and caveats.
+    pub fn with_burst_paste(mut self, options: NcBurstPasteOptions) -> Self {
+        self.paste.set_options(Some(options));
+        self
+    }
+}
+
+/// # Methods
+impl<S: NcInputSource> NcEvents<S> {
+    /// Returns the source of the events.
+    pub fn into_inner(self) -> S {
+        self.source
+    }
+}
+
+/// Reads the next event from `source`, waiting for it up to `timeout`.
+///
+/// Returns `None` if there's no event in time.
+pub(crate) fn read_event<S: NcInputSource + ?Sized>(
+    source: &mut S,
+    timeout: Option<NcTime>,
+) -> NcResult<Option<NcEvent>> {
+    let mut input = NcInput::new_empty();
+    loop {
+        let received = source.get(timeout, Some(&mut input))?;
+        if received == NcReceived::NoInput {
+            return Ok(None);
+        }
+        if let Some(event) = NcEvent::from_input(&input) {
+            return Ok(Some(event));
+        }
+    }
+}
+
+impl<S: NcInputSource> Iterator for NcEvents<S> {
+    type Item = NcResult<NcEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.paste.next(&mut self.source, self.timeout).transpose()
+    }
+}
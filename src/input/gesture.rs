@@ -0,0 +1,371 @@
+//! `NcGesture`
+
+use std::time::{Duration, Instant};
+
+use crate::{NcEvent, NcInputType, NcKey, NcKeyMod};
+
+/// The default maximum time between two clicks of a double click.
+const DEFAULT_DOUBLE_CLICK: Duration = Duration::from_millis(500);
+
+/// A mouse gesture, recognized from a series of mouse [`NcEvent`]s
+/// by [`NcGestures`].
+///
+/// All the coordinates are relative to the terminal's origin. They can be
+/// mapped to the plane under the mouse with
+/// [`NcPlane::hit_test`][crate::NcPlane#method.hit_test].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NcGesture {
+    /// A button was pressed and released without moving.
+    Click {
+        /// The button clicked.
+        button: NcKey,
+        /// The row.
+        y: i32,
+        /// The column.
+        x: i32,
+        /// The modifiers active on release.
+        modifiers: NcKeyMod,
+    },
+
+    /// A button was clicked twice in the same cell in a short time.
+    ///
+    /// The first click is reported as a [`Click`][NcGesture::Click].
+    DoubleClick {
+        /// The button clicked.
+        button: NcKey,
+        /// The row.
+        y: i32,
+        /// The column.
+        x: i32,
+        /// The modifiers active on release.
+        modifiers: NcKeyMod,
+    },
+
+    /// The mouse moved while a button was held down.
+    ///
+    /// It's reported for each movement, starting with the first one
+    /// after the press.
+    Drag {
+        /// The button held down.
+        button: NcKey,
+        /// The row where the button was pressed.
+        from_y: i32,
+        /// The column where the button was pressed.
+        from_x: i32,
+        /// The current row.
+        y: i32,
+        /// The current column.
+        x: i32,
+    },
+
+    /// A button was released after dragging.
+    DragEnd {
+        /// The button released.
+        button: NcKey,
+        /// The row where the button was pressed.
+        from_y: i32,
+        /// The column where the button was pressed.
+        from_x: i32,
+        /// The row where the button was released.
+        y: i32,
+        /// The column where the button was released.
+        x: i32,
+    },
+
+    /// The scroll wheel was moved.
+    Scroll {
+        /// The number of rows scrolled, negative upwards.
+        rows: i32,
+        /// The number of columns scrolled, negative leftwards.
+        cols: i32,
+        /// The row.
+        y: i32,
+        /// The column.
+        x: i32,
+        /// The active modifiers.
+        modifiers: NcKeyMod,
+    },
+}
+
+impl NcGesture {
+    /// Returns the current coordinates of the gesture.
+    pub fn yx(&self) -> (i32, i32) {
+        match *self {
+            NcGesture::Click { y, x, .. }
+            | NcGesture::DoubleClick { y, x, .. }
+            | NcGesture::Drag { y, x, .. }
+            | NcGesture::DragEnd { y, x, .. }
+            | NcGesture::Scroll { y, x, .. } => (y, x),
+        }
+    }
+}
+
+/// A mouse button being held down.
+#[derive(Clone, Copy, Debug)]
+struct Press {
+    button: NcKey,
+    y: i32,
+    x: i32,
+    dragging: bool,
+}
+
+/// A completed click, waiting for a possible second one.
+#[derive(Clone, Copy, Debug)]
+struct Click {
+    button: NcKey,
+    y: i32,
+    x: i32,
+    time: Instant,
+}
+
+/// Recognizes [`NcGesture`]s from mouse [`NcEvent`]s.
+///
+/// # Example
+/// ```no_run
+/// # use libnotcurses_sys::*;
+/// # fn main() -> NcResult<()> {
+/// let mut nc = NcContext::new()?;
+/// nc.mice_enable(NcMiceEvents::All)?;
+/// let mut stdplane = nc.stdplane_handle()?;
+/// let mut gestures = NcGestures::new();
+///
+/// for event in nc.events(None) {
+///     if let Some(NcGesture::Click { y, x, .. }) = gestures.feed(&event?) {
+///         if y >= 0 && x >= 0 {
+///             stdplane.putstr_yx(Some(y as u32), Some(x as u32), "*")?;
+///         }
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct NcGestures {
+    press: Option<Press>,
+    last_click: Option<Click>,
+    double_click: Duration,
+}
+
+/// # Constructors
+impl NcGestures {
+    /// New gesture recognizer, with a default double click time of 500 ms.
+    pub fn new() -> Self {
+        Self::with_double_click(DEFAULT_DOUBLE_CLICK)
+    }
+
+    /// New gesture recognizer, with a custom maximum time between
+    /// the two clicks of a double click.
+    pub fn with_double_click(time: Duration) -> Self {
+        Self { press: None, last_click: None, double_click: time }
+    }
+}
+
+/// # Methods
+impl NcGestures {
+    /// Returns the maximum time between the two clicks of a double click.
+    pub fn double_click(&self) -> Duration {
+        self.double_click
+    }
+
+    /// Sets the maximum time between the two clicks of a double click.
+    pub fn set_double_click(&mut self, time: Duration) {
+        self.double_click = time;
+    }
+
+    /// Forgets any button being held down and the last click.
+    pub fn reset(&mut self) {
+        self.press = None;
+        self.last_click = None;
+    }
+
+    /// Feeds an event to the recognizer, returning the recognized gesture,
+    /// if any.
+    ///
+    /// Events other than mouse events are ignored.
+    pub fn feed(&mut self, event: &NcEvent) -> Option<NcGesture> {
+        self.feed_at(event, Instant::now())
+    }
+
+    /// Feeds an event received at the instant `now` to the recognizer.
+    ///
+    /// See [`feed`][NcGestures#method.feed].
+    pub fn feed_at(&mut self, event: &NcEvent, now: Instant) -> Option<NcGesture> {
+        let (button, kind, y, x, modifiers) = match *event {
+            NcEvent::Mouse { button, kind, y, x, modifiers, .. } => (button, kind, y, x, modifiers),
+            _ => return None,
+        };
+
+        // the scroll wheel doesn't report releases
+        let scroll = match button {
+            NcKey::Button4 => Some((-1, 0)),
+            NcKey::Button5 => Some((1, 0)),
+            NcKey::Button6 => Some((0, -1)),
+            NcKey::Button7 => Some((0, 1)),
+            _ => None,
+        };
+        if let Some((rows, cols)) = scroll {
+            if kind == NcInputType::Release {
+                return None;
+            }
+            return Some(NcGesture::Scroll { rows, cols, y, x, modifiers });
+        }
+
+        // only the release of the button being held down counts, and it's
+        // only a click if it didn't move from where it was pressed
+        if kind == NcInputType::Release {
+            let p = match self.press {
+                Some(p) if p.button == button => p,
+                _ => return None,
+            };
+            self.press = None;
+            if p.dragging {
+                return Some(NcGesture::DragEnd { button, from_y: p.y, from_x: p.x, y, x });
+            }
+            if (p.y, p.x) != (y, x) {
+                return None;
+            }
+            return Some(self.click(button, y, x, modifiers, now));
+        }
+
+        match self.press {
+            // movement while holding a button down
+            Some(ref mut p) if button == p.button || button == NcKey::Motion => {
+                if p.dragging || (y, x) != (p.y, p.x) {
+                    p.dragging = true;
+                    return Some(NcGesture::Drag {
+                        button: p.button,
+                        from_y: p.y,
+                        from_x: p.x,
+                        y,
+                        x,
+                    });
+                }
+                None
+            }
+            _ if button == NcKey::Motion => None,
+            _ => {
+                self.press = Some(Press { button, y, x, dragging: false });
+                None
+            }
+        }
+    }
+
+    /// Returns a click or a double click, depending on the last click.
+    fn click(
+        &mut self,
+        button: NcKey,
+        y: i32,
+        x: i32,
+        modifiers: NcKeyMod,
+        now: Instant,
+    ) -> NcGesture {
+        let double = self.last_click.take().map_or(false, |c| {
+            c.button == button
+                && (c.y, c.x) == (y, x)
+                && now.saturating_duration_since(c.time) <= self.double_click
+        });
+        if double {
+            NcGesture::DoubleClick { button, y, x, modifiers }
+        } else {
+            self.last_click = Some(Click { button, y, x, time: now });
+            NcGesture::Click { button, y, x, modifiers }
+        }
+    }
+}
+
+mod std_impls {
+    use super::NcGestures;
+
+    impl Default for NcGestures {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{NcGesture, NcGestures};
+    use crate::{NcEvent, NcInputType, NcKey, NcKeyMod};
+    use std::time::{Duration, Instant};
+
+    fn mouse(button: NcKey, kind: NcInputType, y: i32, x: i32) -> NcEvent {
+        NcEvent::Mouse { button, kind, y, x, ypx: -1, xpx: -1, modifiers: NcKeyMod::None }
+    }
+
+    #[test]
+    fn clicks() {
+        let mut g = NcGestures::new();
+        let t = Instant::now();
+        let b1 = NcKey::Button1;
+
+        assert_eq![None, g.feed_at(&mouse(b1, NcInputType::Press, 2, 3), t)];
+        assert![matches![
+            g.feed_at(&mouse(b1, NcInputType::Release, 2, 3), t),
+            Some(NcGesture::Click { y: 2, x: 3, .. })
+        ]];
+        g.feed_at(&mouse(b1, NcInputType::Press, 2, 3), t);
+        assert![matches![
+            g.feed_at(&mouse(b1, NcInputType::Release, 2, 3), t),
+            Some(NcGesture::DoubleClick { .. })
+        ]];
+
+        // too late for a double click
+        let late = t + Duration::from_secs(1);
+        g.feed_at(&mouse(b1, NcInputType::Press, 2, 3), t);
+        g.feed_at(&mouse(b1, NcInputType::Release, 2, 3), t);
+        g.feed_at(&mouse(b1, NcInputType::Press, 2, 3), late);
+        assert![matches![
+            g.feed_at(&mouse(b1, NcInputType::Release, 2, 3), late),
+            Some(NcGesture::Click { .. })
+        ]];
+    }
+
+    #[test]
+    fn unmatched_releases() {
+        let mut g = NcGestures::new();
+        let t = Instant::now();
+        let (b1, b3) = (NcKey::Button1, NcKey::Button3);
+
+        // a release without a press
+        assert_eq![None, g.feed_at(&mouse(b1, NcInputType::Release, 2, 3), t)];
+
+        // the release of another button keeps the press
+        g.feed_at(&mouse(b1, NcInputType::Press, 2, 3), t);
+        assert_eq![None, g.feed_at(&mouse(b3, NcInputType::Release, 2, 3), t)];
+        assert![matches![
+            g.feed_at(&mouse(b1, NcInputType::Release, 2, 3), t),
+            Some(NcGesture::Click { .. })
+        ]];
+
+        // a release somewhere else
+        g.feed_at(&mouse(b3, NcInputType::Press, 2, 3), t);
+        assert_eq![None, g.feed_at(&mouse(b3, NcInputType::Release, 5, 3), t)];
+    }
+
+    #[test]
+    fn drag_and_scroll() {
+        let mut g = NcGestures::new();
+        let t = Instant::now();
+        let b1 = NcKey::Button1;
+
+        g.feed_at(&mouse(b1, NcInputType::Press, 1, 1), t);
+        assert_eq![
+            Some(NcGesture::Drag { button: b1, from_y: 1, from_x: 1, y: 1, x: 4 }),
+            g.feed_at(&mouse(b1, NcInputType::Press, 1, 4), t)
+        ];
+        assert_eq![
+            Some(NcGesture::DragEnd { button: b1, from_y: 1, from_x: 1, y: 2, x: 5 }),
+            g.feed_at(&mouse(b1, NcInputType::Release, 2, 5), t)
+        ];
+
+        assert![matches![
+            g.feed_at(&mouse(NcKey::Button5, NcInputType::Press, 0, 0), t),
+            Some(NcGesture::Scroll { rows: 1, cols: 0, .. })
+        ]];
+        assert_eq![
+            None,
+            g.feed_at(&mouse(NcKey::Button5, NcInputType::Release, 0, 0), t)
+        ];
+    }
+}
@@ -15,16 +15,28 @@
 
 use std::ffi::CStr;
 
-use crate::NcKeyMod;
+use crate::{NcKey, NcKeyMod};
 
 pub(crate) mod reimplemented;
 
+pub(crate) mod event;
+pub use event::{NcEvent, NcEvents};
+mod gesture;
+pub use gesture::{NcGesture, NcGestures};
 mod input_type;
 pub use input_type::NcInputType;
 mod mice_events;
 pub use mice_events::NcMiceEvents;
+pub(crate) mod paste;
+pub use paste::NcBurstPasteOptions;
 mod received;
 pub use received::NcReceived;
+mod record;
+pub use record::{NcInputRecorder, NcInputReplayer, NcInputSource};
+#[cfg(all(feature = "async", unix))]
+pub(crate) mod stream;
+#[cfg(all(feature = "async", unix))]
+pub use stream::NcEventStream;
 
 /// Reads and decodes input events.
 ///
@@ -124,6 +136,46 @@ impl NcInput {
             modifiers: modifiers.into(),
         }
     }
+
+    /// New keyboard `NcInput`, with its utf8 representation
+    /// when the key is a character.
+    pub fn with_key(
+        key: impl Into<NcReceived>,
+        modifiers: NcKeyMod,
+        evtype: NcInputType,
+    ) -> NcInput {
+        let key = key.into();
+        let mut input = Self::new_empty();
+        input.id = key.into();
+        input.evtype = evtype.into();
+        input.modifiers = modifiers.into();
+        input.alt = modifiers.alt_p();
+        input.shift = modifiers.shift_p();
+        input.ctrl = modifiers.ctrl_p();
+        input.y = -1;
+        input.x = -1;
+        if let NcReceived::Char(c) = key {
+            let mut buf = [0; 4];
+            for (i, b) in c.encode_utf8(&mut buf).bytes().enumerate() {
+                input.utf8[i] = b as _;
+            }
+        }
+        input
+    }
+
+    /// New mouse `NcInput`, at the absolute coordinates `y`, `x`.
+    pub fn with_mouse(
+        button: NcKey,
+        y: i32,
+        x: i32,
+        modifiers: NcKeyMod,
+        evtype: NcInputType,
+    ) -> NcInput {
+        let mut input = Self::with_key(button, modifiers, evtype);
+        input.y = y;
+        input.x = x;
+        input
+    }
 }
 
 /// # Methods
@@ -0,0 +1,279 @@
+//! `NcBurstPasteOptions`
+
+use std::collections::VecDeque;
+
+use crate::{
+    input::event::read_event, NcEvent, NcInputSource, NcInputType, NcKey, NcKeyMod, NcReceived,
+    NcResult, NcTime,
+};
+
+/// Options for the experimental heuristic that collects bursts of characters
+/// into single [`NcEvent::Paste`] events.
+///
+/// **Experimental**: this is a stopgap, not bracketed-paste support. The
+/// notcurses version these bindings target doesn't report the bracketed-paste
+/// markers (`ESC[200~` and `ESC[201~`) sent by the terminal, so pastes can't
+/// be detected reliably yet. The heuristic may change or be replaced by the
+/// markers once notcurses reports them.
+///
+/// Meanwhile a paste can only be told apart from typing by its timing:
+/// pasted text is written to the terminal's input all at once, so when a
+/// character arrives, the rest of it is already waiting to be read. All the
+/// characters that are immediately available are collected, and if there are
+/// at least [`min_len`] of them, they are reported as a single
+/// [`NcEvent::Paste`], instead of one [`NcEvent::Key`] per character.
+///
+/// Being a heuristic, it can be wrong: a slow or chunked paste can arrive as
+/// several shorter bursts, and fast typing or a key repeat lagging behind can
+/// be taken for a paste. That's why it must be explicitly enabled, e.g. with
+/// [`NcEvents::with_burst_paste`][crate::NcEvents#method.with_burst_paste].
+///
+/// Each paste event holds at most [`max_len`] bytes. Longer pastes are split
+/// into consecutive paste events, so that no input gets lost.
+///
+/// Key presses with modifiers other than `Shift` end the paste. `Enter` and
+/// `Tab` are collected as `'\n'` and `'\t'`. Key releases are dropped.
+///
+/// [`min_len`]: NcBurstPasteOptions#structfield.min_len
+/// [`max_len`]: NcBurstPasteOptions#structfield.max_len
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NcBurstPasteOptions {
+    /// The minimum number of characters arriving together to be considered
+    /// a paste.
+    pub min_len: usize,
+
+    /// The maximum size in bytes of a single paste event.
+    pub max_len: usize,
+}
+
+/// # Constructors
+impl NcBurstPasteOptions {
+    /// The default minimum number of characters of a paste.
+    pub const MIN_LEN: usize = 4;
+
+    /// The default maximum size in bytes of a single paste event (1 MiB).
+    pub const MAX_LEN: usize = 1 << 20;
+
+    /// New `NcBurstPasteOptions` with the default minimum length
+    /// and the provided size limit in bytes.
+    pub fn new(max_len: usize) -> Self {
+        Self { min_len: Self::MIN_LEN, max_len }
+    }
+}
+
+mod std_impls {
+    use super::NcBurstPasteOptions;
+
+    impl Default for NcBurstPasteOptions {
+        fn default() -> Self {
+            Self::new(Self::MAX_LEN)
+        }
+    }
+}
+
+/// Returns the character an event contributes to a paste, if any.
+fn paste_char(event: &NcEvent) -> Option<char> {
+    let ignored_mods = NcKeyMod::Shift | NcKeyMod::CapsLock | NcKeyMod::NumLock;
+    match *event {
+        NcEvent::Key { key, kind, modifiers }
+            if kind != NcInputType::Release && modifiers & !ignored_mods == NcKeyMod::None =>
+        {
+            match key {
+                NcReceived::Char(c) => Some(c),
+                NcReceived::Event(NcKey::Enter) => Some('\n'),
+                NcReceived::Event(NcKey::Tab) => Some('\t'),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Collects bursts of characters into paste events,
+/// when the burst heuristic is enabled.
+#[derive(Debug, Default)]
+pub(crate) struct NcPasteCollector {
+    /// The options, or `None` if the heuristic is disabled.
+    options: Option<NcBurstPasteOptions>,
+    /// Events already read, waiting to be returned.
+    queue: VecDeque<NcEvent>,
+    /// Whether the last paste was split because of its size.
+    continuing: bool,
+}
+
+impl NcPasteCollector {
+    pub(crate) fn set_options(&mut self, options: Option<NcBurstPasteOptions>) {
+        self.options = options;
+    }
+
+    fn pop_or<S>(&mut self, source: &mut S, timeout: Option<NcTime>) -> NcResult<Option<NcEvent>>
+    where
+        S: NcInputSource + ?Sized,
+    {
+        match self.queue.pop_front() {
+            Some(event) => Ok(Some(event)),
+            None => read_event(source, timeout),
+        }
+    }
+
+    /// Returns the next event of `source`, waiting for it up to `timeout`,
+    /// and collecting pastes if enabled.
+    ///
+    /// Returns `None` when there's no event in time.
+    pub(crate) fn next<S>(
+        &mut self,
+        source: &mut S,
+        timeout: Option<NcTime>,
+    ) -> NcResult<Option<NcEvent>>
+    where
+        S: NcInputSource + ?Sized,
+    {
+        let first = match self.pop_or(source, timeout)? {
+            Some(event) => event,
+            None => return Ok(None),
+        };
+        let options = match self.options {
+            Some(options) => options,
+            None => return Ok(Some(first)),
+        };
+        let mut text = match paste_char(&first) {
+            Some(c) => c.to_string(),
+            None => {
+                self.continuing = false;
+                return Ok(Some(first));
+            }
+        };
+
+        let mut chars = 1;
+        let mut burst = vec![first];
+        let mut trailing = None;
+        let mut limited = false;
+        // the rest of the burst is already available
+        while let Some(event) = self.pop_or(source, Some(NcTime::new(0, 0)))? {
+            match paste_char(&event) {
+                Some(c) if text.len() + c.len_utf8() > options.max_len => {
+                    trailing = Some(event);
+                    limited = true;
+                    break;
+                }
+                Some(c) => {
+                    text.push(c);
+                    chars += 1;
+                    burst.push(event);
+                }
+                None if event.is_release() => burst.push(event),
+                None => {
+                    trailing = Some(event);
+                    break;
+                }
+            }
+        }
+
+        let is_paste = self.continuing || chars >= options.min_len;
+        self.continuing = is_paste && limited;
+
+        if let Some(event) = trailing {
+            self.queue.push_front(event);
+        }
+        if is_paste {
+            Ok(Some(NcEvent::Paste(text)))
+        } else {
+            let mut burst = burst.into_iter();
+            let first = burst.next();
+            for event in burst.rev() {
+                self.queue.push_front(event);
+            }
+            Ok(first)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{NcBurstPasteOptions, NcPasteCollector};
+    use crate::{NcEvent, NcInputReplayer, NcInputType, NcKey, NcKeyMod, NcReceived};
+    use std::io::Cursor;
+
+    fn key(key: NcReceived, modifiers: NcKeyMod) -> NcEvent {
+        NcEvent::Key { key, kind: NcInputType::Press, modifiers }
+    }
+
+    /// Returns a replayer of bursts of key presses, a second apart.
+    fn replayer(bursts: &[&[(NcReceived, NcKeyMod)]]) -> NcInputReplayer<Cursor<String>> {
+        let mut recording = String::from("# libnotcurses-sys input recording v2\n");
+        for (second, burst) in bursts.iter().enumerate() {
+            for &(key, modifiers) in burst.iter() {
+                recording += &format![
+                    "{} {} 1 {} -1 -1 -1 -1 -\n",
+                    second * 1000,
+                    u32::from(key),
+                    modifiers.0
+                ];
+            }
+        }
+        NcInputReplayer::new(Cursor::new(recording))
+    }
+
+    fn chars(s: &str) -> Vec<(NcReceived, NcKeyMod)> {
+        s.chars()
+            .map(|c| (NcReceived::Char(c), NcKeyMod::None))
+            .collect()
+    }
+
+    #[test]
+    fn collect_pastes() {
+        let mut c = NcPasteCollector::default();
+        c.set_options(Some(NcBurstPasteOptions::default()));
+
+        let mut burst = chars("hello");
+        burst.push((NcReceived::Event(NcKey::Enter), NcKeyMod::None));
+        burst.push((NcReceived::Char('q'), NcKeyMod::Ctrl));
+        let mut source = replayer(&[&chars("ab"), &burst]);
+
+        // short bursts are returned one by one
+        assert_eq![
+            Some(key(NcReceived::Char('a'), NcKeyMod::None)),
+            c.next(&mut source, None).unwrap()
+        ];
+        assert_eq![
+            Some(key(NcReceived::Char('b'), NcKeyMod::None)),
+            c.next(&mut source, None).unwrap()
+        ];
+        assert_eq![
+            Some(NcEvent::Paste("hello\n".into())),
+            c.next(&mut source, None).unwrap()
+        ];
+        assert_eq![
+            Some(key(NcReceived::Char('q'), NcKeyMod::Ctrl)),
+            c.next(&mut source, None).unwrap()
+        ];
+        assert_eq![
+            Some(key(NcReceived::Event(NcKey::Eof), NcKeyMod::None)),
+            c.next(&mut source, None).unwrap()
+        ];
+    }
+
+    #[test]
+    fn split_long_pastes() {
+        let mut c = NcPasteCollector::default();
+        c.set_options(Some(NcBurstPasteOptions { min_len: 4, max_len: 4 }));
+
+        let mut source = replayer(&[&chars("abcdefghij")]);
+        assert_eq![
+            Some(NcEvent::Paste("abcd".into())),
+            c.next(&mut source, None).unwrap()
+        ];
+        assert_eq![
+            Some(NcEvent::Paste("efgh".into())),
+            c.next(&mut source, None).unwrap()
+        ];
+        assert_eq![
+            Some(NcEvent::Paste("ij".into())),
+            c.next(&mut source, None).unwrap()
+        ];
+        assert_eq![
+            Some(key(NcReceived::Event(NcKey::Eof), NcKeyMod::None)),
+            c.next(&mut source, None).unwrap()
+        ];
+    }
+}
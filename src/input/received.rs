@@ -51,6 +51,18 @@ mod std_impls {
         }
     }
 
+    impl From<NcKey> for NcReceived {
+        fn from(k: NcKey) -> Self {
+            Self::Event(k)
+        }
+    }
+
+    impl From<char> for NcReceived {
+        fn from(c: char) -> Self {
+            Self::from(c as u32)
+        }
+    }
+
     impl From<NcReceived> for u32 {
         fn from(r: NcReceived) -> Self {
             use NcReceived::*;
@@ -0,0 +1,606 @@
+//! `NcInputSource`, `NcInputRecorder` & `NcInputReplayer`
+
+use core::ptr::{null, null_mut};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    c_api, error::clear_errno, Nc, NcContext, NcDirect, NcError, NcErrorKind, NcInput, NcKey,
+    NcKeyMod, NcReceived, NcResult, NcTime,
+};
+
+/// The first line of a recording.
+const HEADER: &str = "# libnotcurses-sys input recording v2";
+
+/// A source of [`NcInput`]s.
+///
+/// It's implemented by [`Nc`], [`NcContext`] (also through a shared
+/// reference) and [`NcDirect`], which read from the terminal, by
+/// [`NcInputRecorder`], which records the inputs of another source, and by
+/// [`NcInputReplayer`], which replays a recording.
+///
+/// Code reading its input through this trait, like [`NcEvents`], can be driven
+/// by a recording in place of the terminal, e.g. to reproduce a bug report.
+///
+/// [`NcEvents`]: crate::NcEvents
+pub trait NcInputSource {
+    /// Reads the next input, waiting for it up to `time`,
+    /// or indefinitely if it's `None`.
+    ///
+    /// Optionally writes the event details in `input`.
+    ///
+    /// Returns [`NcReceived::NoInput`] if the time elapses without input.
+    ///
+    /// See [`Nc::get`][Nc#method.get].
+    fn get(&mut self, time: Option<NcTime>, input: Option<&mut NcInput>) -> NcResult<NcReceived>;
+
+    /// Reads the next input, waiting indefinitely for it.
+    ///
+    /// See [`Nc::get_blocking`][Nc#method.get_blocking].
+    fn get_blocking(&mut self, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        self.get(None, input)
+    }
+
+    /// Reads the next input, only if it's already available.
+    ///
+    /// See [`Nc::get_nblock`][Nc#method.get_nblock].
+    fn get_nblock(&mut self, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        self.get(Some(NcTime::new(0, 0)), input)
+    }
+
+    /// Reads up to `vcount` inputs into `ni`, waiting for the first one up to
+    /// `time`, and returns the number read.
+    ///
+    /// The vector is cleared first.
+    ///
+    /// See [`Nc::getvec`][Nc#method.getvec].
+    fn getvec(
+        &mut self,
+        time: Option<NcTime>,
+        ni: &mut Vec<NcInput>,
+        vcount: u32,
+    ) -> NcResult<u32> {
+        ni.clear();
+        let mut input = NcInput::new_empty();
+        let mut time = time;
+        while ni.len() < vcount as usize {
+            if self.get(time, Some(&mut input))? == NcReceived::NoInput {
+                break;
+            }
+            ni.push(input);
+            time = Some(NcTime::new(0, 0));
+        }
+        Ok(ni.len() as u32)
+    }
+}
+
+impl NcInputSource for Nc {
+    fn get(&mut self, time: Option<NcTime>, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        Nc::get(self, time, input)
+    }
+
+    fn get_blocking(&mut self, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        Nc::get_blocking(self, input)
+    }
+
+    fn get_nblock(&mut self, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        Nc::get_nblock(self, input)
+    }
+
+    fn getvec(
+        &mut self,
+        time: Option<NcTime>,
+        ni: &mut Vec<NcInput>,
+        vcount: u32,
+    ) -> NcResult<u32> {
+        ni.clear();
+        ni.reserve(vcount as usize);
+        let read = Nc::getvec(self, time, ni, vcount)?;
+        // notcurses has initialized the first `read` elements
+        unsafe { ni.set_len(read as usize) };
+        Ok(read)
+    }
+}
+
+impl NcInputSource for NcContext {
+    fn get(&mut self, time: Option<NcTime>, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        NcInputSource::get(&mut **self, time, input)
+    }
+
+    fn get_blocking(&mut self, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        NcInputSource::get_blocking(&mut **self, input)
+    }
+
+    fn get_nblock(&mut self, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        NcInputSource::get_nblock(&mut **self, input)
+    }
+
+    fn getvec(
+        &mut self,
+        time: Option<NcTime>,
+        ni: &mut Vec<NcInput>,
+        vcount: u32,
+    ) -> NcResult<u32> {
+        NcInputSource::getvec(&mut **self, time, ni, vcount)
+    }
+}
+
+impl NcInputSource for &NcContext {
+    fn get(&mut self, time: Option<NcTime>, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        NcContext::get(self, time, input)
+    }
+
+    fn get_blocking(&mut self, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        NcContext::get_blocking(self, input)
+    }
+
+    fn get_nblock(&mut self, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        NcContext::get_nblock(self, input)
+    }
+}
+
+impl NcInputSource for NcDirect {
+    fn get(&mut self, time: Option<NcTime>, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        let ntime = time.as_ref().map_or(null(), |time| time as *const _);
+        let ninput = input.map_or(null_mut(), |input| input as *mut _);
+        // unlike `NcDirect::get`, it doesn't lose the synthesized keys
+        clear_errno();
+        let res = unsafe { c_api::ncdirect_get(self, ntime, ninput) };
+        if res == c_api::NCRESULT_ERR as u32 {
+            Err(NcError::negative_return(
+                "ncdirect_get",
+                c_api::NCRESULT_ERR,
+                &format!["NcDirect.get({:?})", time],
+            ))
+        } else {
+            Ok(NcReceived::from(res))
+        }
+    }
+}
+
+impl<S: NcInputSource + ?Sized> NcInputSource for &mut S {
+    fn get(&mut self, time: Option<NcTime>, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        (**self).get(time, input)
+    }
+
+    fn get_blocking(&mut self, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        (**self).get_blocking(input)
+    }
+
+    fn get_nblock(&mut self, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        (**self).get_nblock(input)
+    }
+
+    fn getvec(
+        &mut self,
+        time: Option<NcTime>,
+        ni: &mut Vec<NcInput>,
+        vcount: u32,
+    ) -> NcResult<u32> {
+        (**self).getvec(time, ni, vcount)
+    }
+}
+
+/// Records every [`NcInput`] read from another [`NcInputSource`],
+/// along with the time elapsed since the recording started.
+///
+/// The whole effective text of each input is recorded as is, even if it
+/// doesn't match its `id`, e.g. with the `Shift` modifier.
+///
+/// Each input is written and flushed as soon as it's read, so the recording
+/// survives a crash of the application. It can be replayed with an
+/// [`NcInputReplayer`].
+///
+/// # Example
+/// ```no_run
+/// # use libnotcurses_sys::*;
+/// # fn main() -> NcResult<()> {
+/// let mut nc = NcContext::new()?;
+/// let mut input = NcInputRecorder::create(&mut *nc, "session.ncrec")?;
+/// while input.get_blocking(None)? != NcReceived::Char('q') {}
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct NcInputRecorder<S: NcInputSource, W: Write> {
+    source: S,
+    writer: W,
+    start: Instant,
+}
+
+/// # Constructors
+impl<S: NcInputSource, W: Write> NcInputRecorder<S, W> {
+    /// New recorder of the inputs of `source`, writing them to `writer`.
+    ///
+    /// The recording starts now.
+    pub fn new(source: S, mut writer: W) -> NcResult<Self> {
+        writeln!(writer, "{}", HEADER)?;
+        writer.flush()?;
+        Ok(Self { source, writer, start: Instant::now() })
+    }
+}
+
+impl<S: NcInputSource> NcInputRecorder<S, BufWriter<File>> {
+    /// New recorder of the inputs of `source`, writing them to a new file
+    /// at `path`, truncating it if it exists.
+    pub fn create<P: AsRef<Path>>(source: S, path: P) -> NcResult<Self> {
+        Self::new(source, BufWriter::new(File::create(path)?))
+    }
+}
+
+/// # Methods
+impl<S: NcInputSource, W: Write> NcInputRecorder<S, W> {
+    /// Returns the recorded source and the writer, ending the recording.
+    pub fn into_inner(self) -> (S, W) {
+        (self.source, self.writer)
+    }
+
+    /// Writes an input to the recording.
+    fn record(&mut self, input: &NcInput) -> NcResult<()> {
+        writeln!(
+            self.writer,
+            "{} {} {} {} {} {} {} {} {}",
+            self.start.elapsed().as_millis(),
+            input.id,
+            input.evtype,
+            input.modifiers,
+            input.y,
+            input.x,
+            input.ypx,
+            input.xpx,
+            encode_text(input),
+        )?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+impl<S: NcInputSource, W: Write> NcInputSource for NcInputRecorder<S, W> {
+    fn get(&mut self, time: Option<NcTime>, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        let mut own = NcInput::new_empty();
+        let received = self.source.get(time, Some(&mut own))?;
+        if received != NcReceived::NoInput {
+            self.record(&own)?;
+        }
+        if let Some(input) = input {
+            *input = own;
+        }
+        Ok(received)
+    }
+}
+
+/// An input recorded by an [`NcInputRecorder`].
+#[derive(Clone, Copy, Debug)]
+struct Record {
+    /// The time since the recording started.
+    time: Duration,
+    input: NcInput,
+}
+
+/// Replays a recording made by an [`NcInputRecorder`] as an [`NcInputSource`].
+///
+/// By default the replay is deterministic and doesn't wait: the timestamps
+/// of the recorded inputs only advance a virtual clock, which is also
+/// advanced by the timeouts of [`get`][NcInputSource#tymethod.get] when the
+/// next input is recorded later than that. So the application sees the same
+/// inputs and the same timeouts in the same order as when it was recorded.
+///
+/// With [`realtime`][NcInputReplayer#method.realtime] it waits instead for
+/// the recorded time of each input to arrive.
+///
+/// Once the recording is exhausted, it returns [`NcKey::Eof`]. It's an error
+/// if the recording doesn't start with the header of the current format.
+///
+/// # Example
+/// ```no_run
+/// # use libnotcurses_sys::{*, widgets::*};
+/// fn replay(selector: &mut NcSelector) -> NcResult<()> {
+///     let mut replay = NcInputReplayer::open("session.ncrec")?;
+///     while selector.offer_next(&mut replay, None)?.is_some() {}
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct NcInputReplayer<R: BufRead> {
+    reader: R,
+    // the number of lines read, including the header
+    line: usize,
+    next: Option<Record>,
+    clock: Duration,
+    realtime: Option<Instant>,
+}
+
+/// # Constructors
+impl<R: BufRead> NcInputReplayer<R> {
+    /// New replayer of the recording read from `reader`.
+    pub fn new(reader: R) -> Self {
+        Self { reader, line: 0, next: None, clock: Duration::ZERO, realtime: None }
+    }
+
+    /// Makes the replayer wait for the recorded time of each input,
+    /// counting from now.
+    pub fn realtime(mut self) -> Self {
+        self.realtime = Some(Instant::now());
+        self
+    }
+}
+
+impl NcInputReplayer<BufReader<File>> {
+    /// New replayer of the recording in the file at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> NcResult<Self> {
+        Ok(Self::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+/// # Methods
+impl<R: BufRead> NcInputReplayer<R> {
+    /// Returns the time elapsed since the beginning of the recording,
+    /// according to the replay.
+    pub fn elapsed(&self) -> Duration {
+        match self.realtime {
+            Some(start) => start.elapsed(),
+            None => self.clock,
+        }
+    }
+
+    /// Returns the next recorded input without consuming it,
+    /// or `None` if the recording is exhausted.
+    fn peek(&mut self) -> NcResult<Option<Record>> {
+        if self.next.is_none() {
+            self.next = self.read_record()?;
+        }
+        Ok(self.next)
+    }
+
+    /// Reads and parses the next record.
+    fn read_record(&mut self) -> NcResult<Option<Record>> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+            let line = line.trim();
+            if self.line == 1 {
+                if line == HEADER {
+                    continue;
+                }
+                return Err(NcError::with_kind(
+                    NcErrorKind::InvalidArgument,
+                    &format!["NcInputReplayer: expected the header {:?}", HEADER],
+                ));
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            return parse_record(line).map(Some).ok_or_else(|| {
+                NcError::with_kind(
+                    NcErrorKind::InvalidArgument,
+                    &format!["NcInputReplayer: invalid record at line {}", self.line],
+                )
+            });
+        }
+    }
+}
+
+/// Returns the UTF-8 text of an input, in hexadecimal, or `-` if it's empty.
+fn encode_text(input: &NcInput) -> String {
+    let text: String = input
+        .utf8
+        .iter()
+        .take_while(|&&b| b != 0)
+        .map(|&b| format!["{:02x}", b as u8])
+        .collect();
+    if text.is_empty() {
+        "-".into()
+    } else {
+        text
+    }
+}
+
+/// Parses the text of an input encoded by [`encode_text`].
+fn decode_text(text: &str) -> Option<String> {
+    if text == "-" {
+        return Some(String::new());
+    }
+    if text.len() % 2 != 0 || !text.is_ascii() {
+        return None;
+    }
+    let bytes = (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// Parses a line of a recording.
+fn parse_record(line: &str) -> Option<Record> {
+    let mut fields = line.split_whitespace();
+    let mut next = || fields.next()?.parse::<i64>().ok();
+    let time = Duration::from_millis(next()? as u64);
+    let mut input = NcInput::new_empty();
+    input.id = next()? as u32;
+    input.evtype = next()? as u32;
+    input.modifiers = next()? as u32;
+    input.y = next()? as i32;
+    input.x = next()? as i32;
+    input.ypx = next()? as i32;
+    input.xpx = next()? as i32;
+
+    let text = decode_text(fields.next()?)?;
+    if fields.next().is_some() {
+        return None;
+    }
+    // the text is nul terminated, and cut at a character boundary
+    let mut len = 0;
+    for c in text.chars() {
+        if len + c.len_utf8() >= input.utf8.len() {
+            break;
+        }
+        len += c.len_utf8();
+    }
+    for (i, b) in text.bytes().take(len).enumerate() {
+        input.utf8[i] = b as _;
+    }
+    let modifiers = NcKeyMod(input.modifiers);
+    input.alt = modifiers.alt_p();
+    input.shift = modifiers.shift_p();
+    input.ctrl = modifiers.ctrl_p();
+    Some(Record { time, input })
+}
+
+impl<R: BufRead> NcInputSource for NcInputReplayer<R> {
+    fn get(&mut self, time: Option<NcTime>, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        let record = match self.peek()? {
+            Some(record) => record,
+            None => {
+                let mut eof = NcInput::new_empty();
+                eof.id = NcKey::Eof.0;
+                if let Some(input) = input {
+                    *input = eof;
+                }
+                return Ok(NcReceived::Event(NcKey::Eof));
+            }
+        };
+
+        // the time to wait for the next input, or `None` if it arrives in time
+        let timeout = time.map(|t| Duration::new(t.tv_sec as u64, t.tv_nsec as u32));
+        let now = self.elapsed();
+        let wait = record.time.saturating_sub(now);
+        if let Some(timeout) = timeout.filter(|&t| t < wait) {
+            match self.realtime {
+                Some(_) => thread::sleep(timeout),
+                None => self.clock = now + timeout,
+            }
+            return Ok(NcReceived::NoInput);
+        }
+        match self.realtime {
+            Some(_) => thread::sleep(wait),
+            None => self.clock = now + wait,
+        }
+
+        self.next = None;
+        if let Some(input) = input {
+            *input = record.input;
+        }
+        Ok(NcReceived::from(record.input.id))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{NcInputRecorder, NcInputReplayer, NcInputSource};
+    use crate::{NcInput, NcInputType, NcKey, NcKeyMod, NcReceived, NcTime};
+    use std::io::Cursor;
+
+    const RECORDING: &str = "# libnotcurses-sys input recording v2
+0 104 1 0 -1 -1 -1 -1 68
+100 105 1 4 -1 -1 -1 -1 69
+1000 1115201 3 0 5 7 -1 -1 -
+";
+
+    #[test]
+    fn replay_with_timeouts() {
+        let mut replay = NcInputReplayer::new(Cursor::new(RECORDING));
+        let mut input = NcInput::new_empty();
+        let ms = |ms| Some(NcTime::new(0, ms * 1_000_000));
+
+        assert_eq![
+            NcReceived::Char('h'),
+            replay.get(ms(10), Some(&mut input)).unwrap()
+        ];
+        assert_eq![NcReceived::NoInput, replay.get(ms(50), None).unwrap()];
+        assert_eq![
+            NcReceived::Char('i'),
+            replay.get(ms(50), Some(&mut input)).unwrap()
+        ];
+        assert![NcKeyMod(input.modifiers).ctrl_p()];
+        assert_eq![NcReceived::NoInput, replay.get_nblock(None).unwrap()];
+        assert_eq![
+            NcReceived::Event(NcKey::Button1),
+            replay.get_blocking(Some(&mut input)).unwrap()
+        ];
+        assert_eq![(5, 7), (input.y, input.x)];
+        assert_eq![NcInputType::Release, NcInputType::from(input.evtype)];
+        assert_eq![1000, replay.elapsed().as_millis()];
+        assert_eq![
+            NcReceived::Event(NcKey::Eof),
+            replay.get_blocking(None).unwrap()
+        ];
+    }
+
+    #[test]
+    fn replay_other_formats() {
+        let v1 = RECORDING.replace(" v2", " v1");
+        assert![NcInputReplayer::new(Cursor::new(v1))
+            .get_blocking(None)
+            .is_err()];
+        // the records must have the text
+        let untexted = "# libnotcurses-sys input recording v2\n0 104 1 0 -1 -1 -1 -1\n";
+        assert![NcInputReplayer::new(Cursor::new(untexted))
+            .get_blocking(None)
+            .is_err()];
+    }
+
+    /// Returns the ids of the inputs of `source` until the end.
+    fn drain(mut source: impl NcInputSource) -> Vec<(u32, u32, i32)> {
+        let mut ids = vec![];
+        let mut input = NcInput::new_empty();
+        while source.get_blocking(Some(&mut input)).unwrap() != NcReceived::Event(NcKey::Eof) {
+            ids.push((input.id, input.modifiers, input.y));
+        }
+        ids
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let replay = NcInputReplayer::new(Cursor::new(RECORDING));
+        let mut recorder = NcInputRecorder::new(replay, Vec::new()).unwrap();
+        let recorded = drain(&mut recorder);
+        assert_eq![3, recorded.len()];
+
+        let (_, written) = recorder.into_inner();
+        let replayed = drain(NcInputReplayer::new(Cursor::new(written)));
+        assert_eq![recorded, replayed];
+    }
+
+    /// Returns the text of the inputs of `source` until the end.
+    fn drain_text(mut source: impl NcInputSource) -> Vec<String> {
+        let mut texts = vec![];
+        let mut input = NcInput::new_empty();
+        while source.get_blocking(Some(&mut input)).unwrap() != NcReceived::Event(NcKey::Eof) {
+            let text = unsafe { std::ffi::CStr::from_ptr(input.utf8.as_ptr()) };
+            texts.push(text.to_string_lossy().into_owned());
+        }
+        texts
+    }
+
+    #[test]
+    fn record_text() {
+        // a shifted key, a combining sequence, a text too long, and no text
+        let recording = "# libnotcurses-sys input recording v2
+0 97 1 1 -1 -1 -1 -1 41
+0 101 1 0 -1 -1 -1 -1 65cc81
+0 101 1 0 -1 -1 -1 -1 65cc81cc81
+0 1115121 1 0 -1 -1 -1 -1 -
+";
+        let expected = vec!["A", "e\u{301}", "e\u{301}", ""];
+        let replay = NcInputReplayer::new(Cursor::new(recording));
+        let mut recorder = NcInputRecorder::new(replay, Vec::new()).unwrap();
+        assert_eq![expected, drain_text(&mut recorder)];
+
+        let (_, written) = recorder.into_inner();
+        let written = String::from_utf8(written).unwrap();
+        assert![written.contains(" 65cc81\n")];
+        assert_eq![
+            expected,
+            drain_text(NcInputReplayer::new(Cursor::new(written)))
+        ];
+    }
+}
@@ -0,0 +1,160 @@
+//! `NcEventStream`
+
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::os::unix::io::{AsRawFd, RawFd};
+
+use async_io::Async;
+use futures_core::Stream;
+
+use crate::{
+    c_api, error::clear_errno, input::paste::NcPasteCollector, Nc, NcBurstPasteOptions, NcError,
+    NcEvent, NcInputSource, NcResult, NcTime,
+};
+
+/// The input file descriptor of a notcurses context.
+///
+/// It's owned by notcurses, so it's not closed when dropped, but its file
+/// status flags are restored to the ones it had when it was wrapped.
+#[derive(Debug)]
+struct NcInputFd {
+    fd: RawFd,
+    flags: c_api::libc::c_int,
+}
+
+impl NcInputFd {
+    /// Wraps `fd`, saving its current file status flags.
+    fn new(fd: RawFd) -> NcResult<Self> {
+        // SAFETY: F_GETFL doesn't modify the descriptor
+        let flags = unsafe { c_api::libc::fcntl(fd, c_api::libc::F_GETFL) };
+        if flags < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        Ok(Self { fd, flags })
+    }
+}
+
+impl AsRawFd for NcInputFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl Drop for NcInputFd {
+    fn drop(&mut self) {
+        // SAFETY: the descriptor is still open, since notcurses owns it
+        unsafe { c_api::libc::fcntl(self.fd, c_api::libc::F_SETFL, self.flags) };
+    }
+}
+
+/// Returns the input file descriptor of the context `nc`.
+///
+/// # Safety
+/// `nc` must point to a valid context.
+pub(crate) unsafe fn nc_input_fd(nc: *mut Nc) -> NcResult<RawFd> {
+    clear_errno();
+    let fd = c_api::notcurses_inputready_fd(nc);
+    if fd < c_api::NCRESULT_OK {
+        return Err(NcError::negative_return(
+            "notcurses_inputready_fd",
+            fd,
+            "NcEventStream.new()",
+        ));
+    }
+    Ok(fd)
+}
+
+/// An asynchronous stream of the input [`NcEvent`]s of a notcurses context,
+/// or of a direct mode one.
+///
+/// It registers the file descriptor returned by
+/// [`Nc::inputready_fd`][Nc#method.inputready_fd] with the
+/// [`async-io`](https://docs.rs/async-io) reactor, and whenever it becomes
+/// readable it drains the pending input from its [`NcInputSource`] without
+/// blocking. This allows to wait for input in any async runtime, alongside
+/// timers and other sources of events, without dedicating a blocking thread
+/// to it.
+///
+/// The stream never ends by itself.
+///
+/// Registering the file descriptor puts it in non-blocking mode
+/// (`O_NONBLOCK`), behind the back of notcurses. Its previous flags are
+/// restored when the stream is dropped, since the mode is shared by every
+/// other user of the descriptor, which might be the stdin of the shell.
+///
+/// It's returned by [`Nc::event_stream`][Nc#method.event_stream],
+/// [`NcContext::event_stream`][crate::NcContext#method.event_stream]
+/// and [`NcDirect::event_stream`][crate::NcDirect#method.event_stream].
+///
+/// *Requires the `async` feature.*
+///
+/// # Example
+/// ```no_run
+/// use futures_lite::StreamExt;
+/// use libnotcurses_sys::*;
+///
+/// # fn main() -> NcResult<()> {
+/// let nc = NcContext::new()?;
+/// futures_lite::future::block_on(async {
+///     let mut events = nc.event_stream()?;
+///     while let Some(event) = events.next().await {
+///         if event?.is_key(NcReceived::Char('q')) {
+///             break;
+///         }
+///     }
+///     Ok(())
+/// })
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct NcEventStream<S: NcInputSource> {
+    // dropped before the source, which might own the descriptor
+    fd: Async<NcInputFd>,
+    source: S,
+    paste: NcPasteCollector,
+}
+
+impl<S: NcInputSource> NcEventStream<S> {
+    /// # Safety
+    /// `fd` must be the input file descriptor of `source`,
+    /// valid for as long as `source` is.
+    pub(crate) unsafe fn new(source: S, fd: RawFd) -> NcResult<Self> {
+        // this sets O_NONBLOCK on the descriptor, until the stream is dropped
+        let fd = Async::new(NcInputFd::new(fd)?)?;
+        Ok(Self { source, fd, paste: NcPasteCollector::default() })
+    }
+
+    /// Enables the experimental heuristic that collects bursts of characters
+    /// into single [`NcEvent::Paste`] events, according to the `options`.
+    ///
+    /// It's disabled by default. See [`NcBurstPasteOptions`] for details
+    /// and caveats.
+    pub fn with_burst_paste(mut self, options: NcBurstPasteOptions) -> Self {
+        self.paste.set_options(Some(options));
+        self
+    }
+}
+
+impl<S: NcInputSource + Unpin> Stream for NcEventStream<S> {
+    type Item = NcResult<NcEvent>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            // drain the already available input first
+            match this.paste.next(&mut this.source, Some(NcTime::new(0, 0))) {
+                Err(e) => return Poll::Ready(Some(Err(e))),
+                Ok(Some(event)) => return Poll::Ready(Some(Ok(event))),
+                Ok(None) => (),
+            }
+            // then wait until there's more
+            match this.fd.poll_readable(cx) {
+                Poll::Ready(Ok(())) => continue,
+                Poll::Ready(Err(e)) => return Poll::Ready(Some(Err(e.into()))),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
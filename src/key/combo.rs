@@ -0,0 +1,399 @@
+//! `NcKeyCombo` & `NcKeyChord`
+
+use crate::{
+    c_api, NcError, NcErrorKind, NcInput, NcInputType, NcKey, NcKeyMod, NcReceived, NcResult,
+};
+
+/// The modifiers taken into account when matching key combinations.
+///
+/// `CapsLock` and `NumLock` are ignored.
+const MATCHED_MODS: NcKeyMod = NcKeyMod(
+    c_api::NCKEY_MOD_SHIFT
+        | c_api::NCKEY_MOD_ALT
+        | c_api::NCKEY_MOD_CTRL
+        | c_api::NCKEY_MOD_SUPER
+        | c_api::NCKEY_MOD_HYPER
+        | c_api::NCKEY_MOD_META,
+);
+
+/// The modifiers in the order they are formatted, with their names.
+const MOD_NAMES: [(NcKeyMod, &str); 6] = [
+    (NcKeyMod::Ctrl, "Ctrl"),
+    (NcKeyMod::Alt, "Alt"),
+    (NcKeyMod::Shift, "Shift"),
+    (NcKeyMod::Super, "Super"),
+    (NcKeyMod::Hyper, "Hyper"),
+    (NcKeyMod::Meta, "Meta"),
+];
+
+/// A key together with its modifiers, like `Ctrl+Alt+Left`.
+///
+/// It can be parsed from a string and formatted back with [`Display`].
+///
+/// A combination is written as zero or more modifiers followed by the key,
+/// separated by `+` or `-`, e.g. `Ctrl+Alt+Left`, `C-x` or `Ctrl++`.
+///
+/// The recognized modifiers are (case-insensitive):
+/// `Ctrl`/`Control`, `Alt`/`Option`, `Shift`, `Super`/`Cmd`/`Win`, `Hyper`
+/// and `Meta`, as well as the Emacs-style case-sensitive abbreviations
+/// `C` (Ctrl), `M` and `A` (Alt), `S` (Shift), `s` (Super) and `H` (Hyper).
+///
+/// The key can be a single character, the [`name`][NcKey#method.name] of an
+/// [`NcKey`] (case-insensitive), `F1`…`F60`, or one of the aliases
+/// `Escape`, `Return`, `Insert`, `Delete`, `PageUp`, `PageDown`, `Space`.
+///
+/// # Example
+/// ```
+/// # use libnotcurses_sys::*;
+/// let combo: NcKeyCombo = "ctrl+alt+left".parse().unwrap();
+/// assert_eq![combo, NcKeyCombo::new(NcKey::Left, NcKeyMod::Ctrl | NcKeyMod::Alt)];
+/// assert_eq!["Ctrl+Alt+Left", combo.to_string()];
+/// ```
+///
+/// [`Display`]: core::fmt::Display
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NcKeyCombo {
+    /// The key.
+    pub key: NcReceived,
+    /// The modifiers.
+    pub modifiers: NcKeyMod,
+}
+
+/// A sequence of one or more [`NcKeyCombo`]s to be pressed one after another,
+/// like `C-x C-s`.
+///
+/// It's written as the key combinations separated by whitespace.
+///
+/// # Example
+/// ```
+/// # use libnotcurses_sys::*;
+/// let chord: NcKeyChord = "C-x C-s".parse().unwrap();
+/// assert_eq![2, chord.combos().len()];
+/// assert_eq!["Ctrl+x Ctrl+s", chord.to_string()];
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct NcKeyChord(Vec<NcKeyCombo>);
+
+/// # Constructors
+impl NcKeyCombo {
+    /// New key combination.
+    pub fn new(key: impl Into<NcReceived>, modifiers: NcKeyMod) -> Self {
+        Self { key: key.into(), modifiers }
+    }
+
+    /// Returns the key combination of an [`NcInput`],
+    /// or `None` if it represents no input.
+    pub fn from_input(input: &NcInput) -> Option<Self> {
+        match NcReceived::from(input.id) {
+            NcReceived::NoInput => None,
+            key => Some(Self { key, modifiers: NcKeyMod(input.modifiers) & MATCHED_MODS }),
+        }
+    }
+}
+
+/// # Methods
+impl NcKeyCombo {
+    /// Returns true if the `input` matches this key combination.
+    ///
+    /// `CapsLock` and `NumLock` are ignored. For character keys, letters are
+    /// compared case-insensitively when the combination includes `Ctrl` or
+    /// `Shift`, and `Shift` is ignored unless the combination includes it,
+    /// since it's already reflected in the received character.
+    ///
+    /// Releases never match.
+    pub fn matches(&self, input: &NcInput) -> bool {
+        if NcInputType::from(input.evtype) == NcInputType::Release {
+            return false;
+        }
+        Self::from_input(input).map_or(false, |combo| self.matches_combo(&combo))
+    }
+
+    /// Returns true if the `received` combination matches this one.
+    ///
+    /// See [`matches`][NcKeyCombo#method.matches].
+    pub fn matches_combo(&self, received: &NcKeyCombo) -> bool {
+        let own = self.modifiers & MATCHED_MODS;
+        let mut other = received.modifiers & MATCHED_MODS;
+        match (self.key, received.key) {
+            (NcReceived::Char(a), NcReceived::Char(b)) => {
+                if !own.shift_p() {
+                    other = other & !NcKeyMod::Shift;
+                }
+                let same_key =
+                    if own.ctrl_p() || own.shift_p() { a.eq_ignore_ascii_case(&b) } else { a == b };
+                same_key && own == other
+            }
+            (a, b) => a == b && own == other,
+        }
+    }
+
+    /// Returns true if the key is itself a modifier key, e.g. [`NcKey::LShift`].
+    pub fn is_modifier_key(&self) -> bool {
+        matches!(self.key, NcReceived::Event(k)
+            if (c_api::NCKEY_LSHIFT..=c_api::NCKEY_RMETA).contains(&k.0))
+    }
+}
+
+/// # Constructors
+impl NcKeyChord {
+    /// New key chord from a list of key combinations.
+    ///
+    /// Returns an error if the list is empty.
+    pub fn new(combos: Vec<NcKeyCombo>) -> NcResult<Self> {
+        if combos.is_empty() {
+            Err(NcError::with_kind(
+                NcErrorKind::InvalidArgument,
+                "NcKeyChord::new(): empty chord",
+            ))
+        } else {
+            Ok(Self(combos))
+        }
+    }
+}
+
+/// # Methods
+impl NcKeyChord {
+    /// Returns the key combinations of this chord.
+    pub fn combos(&self) -> &[NcKeyCombo] {
+        &self.0
+    }
+
+    /// Returns true if the `received` key combinations match the beginning
+    /// of this chord.
+    pub fn starts_with(&self, received: &[NcKeyCombo]) -> bool {
+        received.len() <= self.0.len()
+            && self
+                .0
+                .iter()
+                .zip(received)
+                .all(|(own, r)| own.matches_combo(r))
+    }
+
+    /// Returns true if the `received` key combinations match this whole chord.
+    pub fn matches_combos(&self, received: &[NcKeyCombo]) -> bool {
+        received.len() == self.0.len() && self.starts_with(received)
+    }
+}
+
+/// Parses a modifier name.
+fn parse_modifier(name: &str) -> Option<NcKeyMod> {
+    // emacs style abbreviations are case-sensitive
+    match name {
+        "C" => return Some(NcKeyMod::Ctrl),
+        "M" | "A" => return Some(NcKeyMod::Alt),
+        "S" => return Some(NcKeyMod::Shift),
+        "s" => return Some(NcKeyMod::Super),
+        "H" => return Some(NcKeyMod::Hyper),
+        _ => (),
+    }
+    match name.to_ascii_lowercase().as_str() {
+        "ctrl" | "control" => Some(NcKeyMod::Ctrl),
+        "alt" | "option" => Some(NcKeyMod::Alt),
+        "shift" => Some(NcKeyMod::Shift),
+        "super" | "cmd" | "win" => Some(NcKeyMod::Super),
+        "hyper" => Some(NcKeyMod::Hyper),
+        "meta" => Some(NcKeyMod::Meta),
+        _ => None,
+    }
+}
+
+/// Parses a key name.
+fn parse_key(name: &str) -> Option<NcReceived> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(NcReceived::from(c));
+    }
+    let lower = name.to_ascii_lowercase();
+    let key = match lower.as_str() {
+        "space" => return Some(NcReceived::Char(' ')),
+        "escape" => NcKey::Esc,
+        "return" => NcKey::Enter,
+        "insert" => NcKey::Ins,
+        "delete" => NcKey::Del,
+        "pageup" => NcKey::PgUp,
+        "pagedown" => NcKey::PgDown,
+        _ => {
+            if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u32>().ok()) {
+                if n <= 60 {
+                    return Some(NcReceived::Event(NcKey(c_api::NCKEY_F00 + n)));
+                }
+            }
+            let named = [NcKey::Tab.0, NcKey::Esc.0]
+                .into_iter()
+                .chain(c_api::NCKEY_INVALID..=c_api::NCKEY_EOF)
+                .find(|&n| NcKey::check_name(n).eq_ignore_ascii_case(name));
+            return named.map(|n| NcReceived::Event(NcKey(n)));
+        }
+    };
+    Some(NcReceived::Event(key))
+}
+
+mod std_impls {
+    use super::{parse_key, parse_modifier, NcKeyChord, NcKeyCombo, MOD_NAMES};
+    use crate::{c_api, NcError, NcErrorKind, NcKeyMod, NcReceived};
+    use core::{fmt, str::FromStr};
+
+    fn invalid(kind: &str, s: &str) -> NcError {
+        NcError::with_kind(
+            NcErrorKind::InvalidArgument,
+            &format!["invalid {}: {:?}", kind, s],
+        )
+    }
+
+    impl FromStr for NcKeyCombo {
+        type Err = NcError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let is_sep = |c: char| c == '+' || c == '-';
+            let s = s.trim();
+            if s.is_empty() || s.contains(char::is_whitespace) {
+                return Err(invalid("key combination", s));
+            }
+
+            // the key may itself be a separator, e.g. "Ctrl++" or "C--"
+            let (mods, key) = match s.char_indices().rev().nth(1) {
+                Some((i, c)) if is_sep(c) => (&s[..i], &s[i + 1..]),
+                _ => match s.rfind(is_sep) {
+                    Some(i) if i + 1 < s.len() => (&s[..i], &s[i + 1..]),
+                    Some(_) if s.len() == 1 => ("", s),
+                    Some(_) => return Err(invalid("key combination", s)),
+                    None => ("", s),
+                },
+            };
+
+            let mut modifiers = NcKeyMod::None;
+            if !mods.is_empty() {
+                for name in mods.split(is_sep) {
+                    modifiers |= parse_modifier(name).ok_or_else(|| invalid("modifier", name))?;
+                }
+            }
+            let key = parse_key(key).ok_or_else(|| invalid("key", key))?;
+            Ok(Self { key, modifiers })
+        }
+    }
+
+    impl FromStr for NcKeyChord {
+        type Err = NcError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let combos = s
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<Vec<_>, _>>()?;
+            if combos.is_empty() {
+                return Err(invalid("key chord", s));
+            }
+            Ok(Self(combos))
+        }
+    }
+
+    impl fmt::Display for NcKeyCombo {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            for (modifier, name) in MOD_NAMES {
+                if self.modifiers & modifier != NcKeyMod::None {
+                    write!(f, "{}+", name)?;
+                }
+            }
+            match self.key {
+                NcReceived::Char(' ') => write!(f, "Space"),
+                NcReceived::Char(c) => write!(f, "{}", c),
+                NcReceived::Event(k) if (c_api::NCKEY_F00..=c_api::NCKEY_F60).contains(&k.0) => {
+                    write!(f, "F{}", k.0 - c_api::NCKEY_F00)
+                }
+                NcReceived::Event(k) => match k.name() {
+                    "" => write!(f, "{:?}", k),
+                    name => write!(f, "{}", name),
+                },
+                NcReceived::NoInput => write!(f, "NoInput"),
+            }
+        }
+    }
+
+    impl fmt::Display for NcKeyChord {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            for (i, combo) in self.0.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{}", combo)?;
+            }
+            Ok(())
+        }
+    }
+
+    impl From<NcKeyCombo> for NcKeyChord {
+        fn from(combo: NcKeyCombo) -> Self {
+            Self(vec![combo])
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{NcKeyChord, NcKeyCombo};
+    use crate::{NcKey, NcKeyMod, NcReceived};
+
+    #[test]
+    fn parse_combos() {
+        let c: NcKeyCombo = "Ctrl+Alt+Left".parse().unwrap();
+        assert_eq![
+            NcKeyCombo::new(NcKey::Left, NcKeyMod::Ctrl | NcKeyMod::Alt),
+            c
+        ];
+
+        let c: NcKeyCombo = "C-x".parse().unwrap();
+        assert_eq![NcKeyCombo::new('x', NcKeyMod::Ctrl), c];
+
+        let c: NcKeyCombo = "ctrl++".parse().unwrap();
+        assert_eq![NcKeyCombo::new('+', NcKeyMod::Ctrl), c];
+
+        let c: NcKeyCombo = "-".parse().unwrap();
+        assert_eq![NcKeyCombo::new('-', NcKeyMod::None), c];
+
+        let c: NcKeyCombo = "s-f5".parse().unwrap();
+        assert_eq![NcKeyCombo::new(NcKey::F05, NcKeyMod::Super), c];
+
+        let c: NcKeyCombo = "M-Escape".parse().unwrap();
+        assert_eq![NcKeyCombo::new(NcKey::Esc, NcKeyMod::Alt), c];
+
+        let c: NcKeyCombo = "pgup".parse().unwrap();
+        assert_eq![NcReceived::Event(NcKey::PgUp), c.key];
+
+        assert!["Ctrl+".parse::<NcKeyCombo>().is_err()];
+        assert!["Foo+x".parse::<NcKeyCombo>().is_err()];
+        assert!["Ctrl+Nope".parse::<NcKeyCombo>().is_err()];
+    }
+
+    #[test]
+    fn format_round_trip() {
+        for s in [
+            "Ctrl+Alt+Left",
+            "Shift+F12",
+            "Ctrl+Space",
+            "Alt+Tab",
+            "Ctrl++",
+            "q",
+        ] {
+            let c: NcKeyCombo = s.parse().unwrap();
+            assert_eq![s, c.to_string()];
+        }
+        let chord: NcKeyChord = "C-x  C-s".parse().unwrap();
+        assert_eq!["Ctrl+x Ctrl+s", chord.to_string()];
+        assert![" ".parse::<NcKeyChord>().is_err()];
+    }
+
+    #[test]
+    fn match_combos() {
+        let ctrl_s: NcKeyCombo = "C-s".parse().unwrap();
+        assert![ctrl_s.matches_combo(&NcKeyCombo::new('S', NcKeyMod::Ctrl))];
+        assert![!ctrl_s.matches_combo(&NcKeyCombo::new('s', NcKeyMod::None))];
+
+        let upper_a: NcKeyCombo = "A".parse().unwrap();
+        assert![upper_a.matches_combo(&NcKeyCombo::new('A', NcKeyMod::Shift))];
+        assert![!upper_a.matches_combo(&NcKeyCombo::new('a', NcKeyMod::None))];
+
+        let left = NcKeyCombo::new(NcKey::Left, NcKeyMod::None);
+        assert![!left.matches_combo(&NcKeyCombo::new(NcKey::Left, NcKeyMod::Shift))];
+        assert![left.matches_combo(&NcKeyCombo::new(NcKey::Left, NcKeyMod::NumLock))];
+    }
+}
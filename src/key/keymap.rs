@@ -0,0 +1,265 @@
+//! `NcKeymap`
+
+use std::time::{Duration, Instant};
+
+use crate::{NcInput, NcInputType, NcKey, NcKeyChord, NcKeyCombo, NcReceived, NcResult};
+
+/// The default time allowed between the keys of a chord.
+const DEFAULT_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Maps key chords to user-defined actions.
+///
+/// Incoming [`NcInput`]s are [`feed`][NcKeymap#method.feed]ed to the keymap,
+/// which keeps track of the keys received so far, and reports whether they
+/// complete a bound chord, are the beginning of one, or don't match any.
+///
+/// If more than the [`timeout`][NcKeymap#method.timeout] elapses between two
+/// keys of a chord, the keys received before are discarded.
+///
+/// When a chord is also the beginning of a longer one, the shorter one wins.
+///
+/// # Example
+/// ```
+/// # use libnotcurses_sys::*;
+/// # fn main() -> NcResult<()> {
+/// #[derive(Debug, PartialEq)]
+/// enum Action { Save, Quit }
+///
+/// let mut keymap = NcKeymap::new();
+/// keymap.bind("C-x C-s", Action::Save)?;
+/// keymap.bind("Ctrl+q", Action::Quit)?;
+///
+/// let ctrl_x = NcInput::with_all_args('X', None, None, NcKeyMod::Ctrl, NcInputType::Press);
+/// let ctrl_s = NcInput::with_all_args('S', None, None, NcKeyMod::Ctrl, NcInputType::Press);
+/// assert_eq![NcKeymapMatch::Pending, keymap.feed(&ctrl_x)];
+/// assert_eq![NcKeymapMatch::Action(&Action::Save), keymap.feed(&ctrl_s)];
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct NcKeymap<A> {
+    bindings: Vec<(NcKeyChord, A)>,
+    pending: Vec<NcKeyCombo>,
+    last: Option<Instant>,
+    timeout: Duration,
+}
+
+/// The result of [`feed`][NcKeymap#method.feed]ing an input to an [`NcKeymap`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NcKeymapMatch<'a, A> {
+    /// The input doesn't match any bound chord.
+    ///
+    /// It's also returned for inputs the keymap ignores: key releases,
+    /// mouse motion and presses of the modifier keys by themselves.
+    None,
+
+    /// The input is part of a bound chord, waiting for more keys.
+    Pending,
+
+    /// The input completed a chord, bound to this action.
+    Action(&'a A),
+}
+
+/// # Constructors
+impl<A> NcKeymap<A> {
+    /// New empty keymap, with the default timeout of 1 second between keys.
+    pub fn new() -> Self {
+        Self::with_timeout(DEFAULT_TIMEOUT)
+    }
+
+    /// New empty keymap, with a custom `timeout` between keys.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self { bindings: vec![], pending: vec![], last: None, timeout }
+    }
+}
+
+/// # Methods
+impl<A> NcKeymap<A> {
+    /// Binds the `chord` to an `action`, parsing it from a string.
+    ///
+    /// Any previous binding of the same chord is replaced.
+    ///
+    /// See [`NcKeyCombo`] and [`NcKeyChord`] for the syntax.
+    pub fn bind(&mut self, chord: &str, action: A) -> NcResult<()> {
+        self.bind_chord(chord.parse()?, action);
+        Ok(())
+    }
+
+    /// Binds the `chord` to an `action`.
+    ///
+    /// Any previous binding of the same chord is replaced.
+    pub fn bind_chord(&mut self, chord: NcKeyChord, action: A) {
+        match self.bindings.iter_mut().find(|(c, _)| *c == chord) {
+            Some(binding) => binding.1 = action,
+            None => self.bindings.push((chord, action)),
+        }
+    }
+
+    /// Removes the binding of the `chord`, returning its action.
+    pub fn unbind(&mut self, chord: &NcKeyChord) -> Option<A> {
+        let index = self.bindings.iter().position(|(c, _)| c == chord)?;
+        Some(self.bindings.remove(index).1)
+    }
+
+    /// Returns the action bound to the `chord`, if any.
+    pub fn action(&self, chord: &NcKeyChord) -> Option<&A> {
+        self.bindings
+            .iter()
+            .find(|(c, _)| c == chord)
+            .map(|(_, a)| a)
+    }
+
+    /// Returns an iterator over the bound chords and their actions.
+    pub fn bindings(&self) -> impl Iterator<Item = (&NcKeyChord, &A)> {
+        self.bindings.iter().map(|(c, a)| (c, a))
+    }
+
+    /// Returns the time allowed between the keys of a chord.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Sets the time allowed between the keys of a chord.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Returns the keys of the chord in progress.
+    pub fn pending(&self) -> &[NcKeyCombo] {
+        &self.pending
+    }
+
+    /// Discards the keys of the chord in progress.
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.last = None;
+    }
+
+    /// Feeds an input to the keymap, returning whether it matches a chord.
+    pub fn feed(&mut self, input: &NcInput) -> NcKeymapMatch<'_, A> {
+        self.feed_at(input, Instant::now())
+    }
+
+    /// Feeds an input received at the instant `now` to the keymap.
+    ///
+    /// See [`feed`][NcKeymap#method.feed].
+    pub fn feed_at(&mut self, input: &NcInput, now: Instant) -> NcKeymapMatch<'_, A> {
+        if NcInputType::from(input.evtype) == NcInputType::Release {
+            return NcKeymapMatch::None;
+        }
+        let combo = match NcKeyCombo::from_input(input) {
+            Some(c) if c.key != NcReceived::Event(NcKey::Motion) && !c.is_modifier_key() => c,
+            _ => return NcKeymapMatch::None,
+        };
+
+        if let Some(last) = self.last {
+            if now.saturating_duration_since(last) > self.timeout {
+                self.pending.clear();
+            }
+        }
+        self.last = Some(now);
+        self.pending.push(combo);
+
+        loop {
+            if let Some(index) = self
+                .bindings
+                .iter()
+                .position(|(c, _)| c.matches_combos(&self.pending))
+            {
+                self.reset();
+                return NcKeymapMatch::Action(&self.bindings[index].1);
+            }
+            if self
+                .bindings
+                .iter()
+                .any(|(c, _)| c.starts_with(&self.pending))
+            {
+                return NcKeymapMatch::Pending;
+            }
+            // a broken chord: retry with the last key alone
+            if self.pending.len() > 1 {
+                self.pending.drain(..self.pending.len() - 1);
+                continue;
+            }
+            self.reset();
+            return NcKeymapMatch::None;
+        }
+    }
+}
+
+mod std_impls {
+    use super::NcKeymap;
+
+    impl<A> Default for NcKeymap<A> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{NcKeymap, NcKeymapMatch};
+    use crate::{NcInput, NcInputType, NcKey, NcKeyMod};
+    use std::time::{Duration, Instant};
+
+    fn key(id: char, modifiers: NcKeyMod) -> NcInput {
+        NcInput::with_all_args(id, None, None, modifiers, NcInputType::Press)
+    }
+
+    #[test]
+    fn chords() {
+        let mut keymap = NcKeymap::new();
+        keymap.bind("C-x C-s", 1).unwrap();
+        keymap.bind("C-x k", 2).unwrap();
+        keymap.bind("q", 3).unwrap();
+
+        assert_eq![
+            NcKeymapMatch::Pending,
+            keymap.feed(&key('X', NcKeyMod::Ctrl))
+        ];
+        assert_eq![
+            NcKeymapMatch::Action(&2),
+            keymap.feed(&key('k', NcKeyMod::None))
+        ];
+        assert![keymap.pending().is_empty()];
+
+        // a broken chord retries the last key by itself
+        assert_eq![
+            NcKeymapMatch::Pending,
+            keymap.feed(&key('X', NcKeyMod::Ctrl))
+        ];
+        assert_eq![
+            NcKeymapMatch::Action(&3),
+            keymap.feed(&key('q', NcKeyMod::None))
+        ];
+        assert_eq![NcKeymapMatch::None, keymap.feed(&key('z', NcKeyMod::None))];
+
+        // modifier keys alone are ignored
+        let mut lctrl = key(' ', NcKeyMod::Ctrl);
+        lctrl.id = NcKey::LCtrl.0;
+        assert_eq![
+            NcKeymapMatch::Pending,
+            keymap.feed(&key('X', NcKeyMod::Ctrl))
+        ];
+        assert_eq![NcKeymapMatch::None, keymap.feed(&lctrl)];
+        assert_eq![1, keymap.pending().len()];
+    }
+
+    #[test]
+    fn chord_timeout() {
+        let mut keymap = NcKeymap::with_timeout(Duration::from_millis(100));
+        keymap.bind("C-x C-s", ()).unwrap();
+
+        let start = Instant::now();
+        let late = start + Duration::from_millis(200);
+        assert_eq![
+            NcKeymapMatch::Pending,
+            keymap.feed_at(&key('X', NcKeyMod::Ctrl), start)
+        ];
+        assert_eq![
+            NcKeymapMatch::None,
+            keymap.feed_at(&key('S', NcKeyMod::Ctrl), late)
+        ];
+    }
+}
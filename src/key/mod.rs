@@ -10,10 +10,17 @@
 
 pub(crate) mod reimplemented;
 
+mod combo;
 #[allow(clippy::module_inception)]
 mod key;
+mod keymap;
 mod keymod;
-pub use {key::NcKey, keymod::NcKeyMod};
+pub use {
+    combo::{NcKeyChord, NcKeyCombo},
+    key::NcKey,
+    keymap::{NcKeymap, NcKeymapMatch},
+    keymod::NcKeyMod,
+};
 
 pub(crate) mod c_api {
     pub use super::key::c_api::*;
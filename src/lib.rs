@@ -20,13 +20,21 @@
 //!
 //! ### Notes on the Rust API
 //!
-//! The `Drop` trait is not implemented for any wrapping type in this library
-//! over structures created by the underlying C library.
+//! The `Drop` trait is not implemented for the type aliases over structures
+//! created by the underlying C library.
 //!
 //! This means you still have to manually call the `stop()` method for `Nc`
 //! and `NcDirect` objects, and the `destroy()` method for the rest of types that
 //! allocate, (like `NcPlane`, `NcMenu`…) at the end of their scope.
 //!
+//! Alternatively you can use the owned [`NcContext`], which stops notcurses
+//! when it goes out of scope, and dereferences to [`Nc`]. Its planes can be
+//! accessed through [`NcPlaneHandle`]s, which borrow the context (or their
+//! parent plane), so that they can't be used after being destroyed.
+//! Likewise, the owned [`NcDirectContext`] restores the terminal and stops
+//! the direct mode context when it goes out of scope, and the
+//! [`NcWidgetHandle`][widgets::NcWidgetHandle]s destroy their widgets.
+//!
 //! But they do implement methods and use `NcResult` as the return type,
 //! for handling errors in the way we are used to in Rust.
 //!
@@ -86,22 +94,28 @@ mod error;
 mod fade;
 mod fd;
 mod file;
+mod grid;
 mod input;
 mod key;
 mod log_level;
 mod macros;
+mod markup;
 mod metric;
 mod notcurses;
 mod palette;
+mod panic_hook;
 mod pixel;
 mod plane;
 mod resizecb;
 mod rgb;
 mod scale;
+mod split;
 mod stats;
 mod string;
 mod style;
+mod text_layout;
 mod time;
+mod unicode;
 mod visual;
 
 pub mod widgets;
@@ -117,25 +131,39 @@ pub use blitter::NcBlitter;
 pub use capabilities::NcCapabilities;
 pub use cell::NcCell;
 pub use channel::{NcChannel, NcChannels};
-pub use direct::{NcDirect, NcDirectFlags};
-pub use error::{NcError, NcResult};
+pub use direct::{NcDirect, NcDirectContext, NcDirectFlags};
+pub use error::{NcError, NcErrorKind, NcResult};
 pub use fade::{NcFadeCb, NcFadeCtx};
 pub use fd::{NcFdPlane, NcFdPlaneOptions, NcSubproc, NcSubprocOptions};
 pub use file::NcFile;
-pub use input::{NcInput, NcInputType, NcMiceEvents, NcReceived};
-pub use key::{NcKey, NcKeyMod};
+pub use grid::{NcGrid, NcGridCell, NcGridChange, NcGridDiff};
+#[cfg(all(feature = "async", unix))]
+pub use input::NcEventStream;
+pub use input::{
+    NcBurstPasteOptions, NcEvent, NcEvents, NcGesture, NcGestures, NcInput, NcInputRecorder,
+    NcInputReplayer, NcInputSource, NcInputType, NcMiceEvents, NcReceived,
+};
+pub use key::{NcKey, NcKeyChord, NcKeyCombo, NcKeyMod, NcKeymap, NcKeymapMatch};
 pub use log_level::NcLogLevel;
-pub use notcurses::{Nc, NcFlags, NcOptions};
+pub use markup::{NcMarkup, NcSpan, NcSpanColor};
+#[cfg(unix)]
+pub use notcurses::NcHeadless;
+pub use notcurses::{Nc, NcContext, NcFlags, NcOptions};
 pub use palette::{NcPalette, NcPaletteIndex};
 pub use pixel::{NcPixel, NcPixelGeometry, NcPixelImpl};
-pub use plane::{NcPlane, NcPlaneFlags, NcPlaneOptions, NcPlaneOptionsBuilder};
+pub use plane::{
+    NcHit, NcPlane, NcPlaneFlags, NcPlaneHandle, NcPlaneIter, NcPlaneIterMut, NcPlaneOptions,
+    NcPlaneOptionsBuilder, NcPlaneWriter,
+};
 pub use r#box::NcBoxMask;
-pub use resizecb::NcResizeCb;
+pub use resizecb::{NcResizeCb, NcResizeStrategy};
 pub use rgb::{NcRgb, NcRgba};
 pub use scale::NcScale;
+pub use split::{NcConstraint, NcSplit, NcSplitDirection};
 pub use stats::NcStats;
 pub use string::NcString;
 pub use style::NcStyle;
+pub use text_layout::{NcTextLayout, NcTextLine};
 pub use time::NcTime;
 pub use visual::{
     NcVisual, NcVisualFlags, NcVisualGeometry, NcVisualOptions, NcVisualOptionsBuilder,
@@ -251,21 +251,38 @@ macro_rules! putstrln {
 ///
 /// In other words:
 /// Returns Ok(`$ok`) if `$res` >= [NCRESULT_OK], otherwise returns
-/// Err([NcError]::[new][NcError#method.new](`$res`, `$msg`)).
+/// Err([NcError]::[negative_return][NcError#method.negative_return](`$res`, `$msg`)).
 ///
 /// `$ok` & `$msg` are optional. By default they will be the unit
 /// type `()`, and an empty `&str` `""`, respectively.
+///
+/// The name of the failing C function is taken from the `$res` expression,
+/// which is then evaluated after clearing `errno`.
+///
+/// When `$res` is a variable, the name must be given with a leading
+/// `@fn "name",`, and `errno` must have been cleared with
+/// [`clear_errno`][crate::error::clear_errno] before calling the C function.
 #[macro_export]
 #[doc(hidden)]
 macro_rules! error {
-    ($res:expr, $msg:expr, $ok:expr) => {{
+    (@fn $fn:expr, $res:expr, $msg:expr, $ok:expr) => {{
         let res = $res;
         if res >= crate::c_api::NCRESULT_OK {
             return Ok($ok);
         } else {
-            return Err(crate::NcError::with_msg(res, $msg));
+            let mut err = crate::NcError::negative_return($fn, res, "");
+            err.msg = $msg.to_string();
+            return Err(err);
         }
     }};
+    (@fn $fn:expr, $res:expr, $msg:expr) => {
+        error![@fn $fn, $res, $msg, ()]
+    };
+    ($res:expr, $msg:expr, $ok:expr) => {{
+        crate::error::clear_errno();
+        let res = $res;
+        error![@fn crate::error::c_function_name(stringify!($res)), res, $msg, $ok]
+    }};
     ($res:expr, $msg:expr) => {
         error![$res, $msg, ()]
     };
@@ -279,28 +296,36 @@ macro_rules! error {
 ///
 /// In other words:
 /// Returns Ok(&*`$ptr`) if `!$ptr.is_null()`, otherwise returns
-/// Err([NcError]]::[new][NcError#method.new]([NCRESULT_ERR], `$msg`)).
+/// Err([NcError]]::[null_returned][NcError#method.null_returned](`$msg`)).
 ///
 /// `$msg` is optional. By default it will be an empty `&str` `""`.
 #[macro_export]
 #[doc(hidden)]
 macro_rules! error_ref {
-    ($ptr:expr, $msg:expr, $ok:expr) => {{
-        let ptr = $ptr; // avoid calling a function multiple times
-        if ptr.is_null() {
-            return Err(crate::NcError::with_msg(crate::c_api::NCRESULT_ERR, $msg));
+    (@fn $fn:expr, $ptr:expr, $msg:expr, $ok:expr) => {{
+        if $ptr.is_null() {
+            let mut err = crate::NcError::null_returned($fn, "");
+            err.msg = $msg.to_string();
+            return Err(err);
         } else {
             #[allow(unused_unsafe)]
             return Ok(unsafe { $ok });
         }
     }};
+    ($ptr:expr, $msg:expr, $ok:expr) => {{
+        crate::error::clear_errno();
+        let ptr = $ptr; // avoid calling a function multiple times
+        error_ref![@fn crate::error::c_function_name(stringify!($ptr)), ptr, $msg, $ok];
+    }};
     ($ptr:expr, $msg:expr) => {{
+        crate::error::clear_errno();
         let ptr = $ptr;
-        error_ref![$ptr, $msg, unsafe { &*ptr }];
+        error_ref![@fn crate::error::c_function_name(stringify!($ptr)), ptr, $msg, unsafe { &*ptr }];
     }};
     ($ptr:expr) => {{
+        crate::error::clear_errno();
         let ptr = $ptr;
-        error_ref![$ptr, "", unsafe { &*ptr }];
+        error_ref![@fn crate::error::c_function_name(stringify!($ptr)), ptr, "", unsafe { &*ptr }];
     }};
 }
 
@@ -309,28 +334,46 @@ macro_rules! error_ref {
 ///
 /// In other words:
 /// Returns Ok(&mut *`$ptr`) if `!$ptr._is_null()`, otherwise returns
-/// Err([NcError]]::[new][NcError#method.new]([NCRESULT_ERR], `$msg`)).
+/// Err([NcError]]::[null_returned][NcError#method.null_returned](`$msg`)).
 ///
 /// `$msg` is optional. By default it will be an empty `&str` `""`.
 #[macro_export]
 #[doc(hidden)]
 macro_rules! error_ref_mut {
-    ($ptr:expr, $msg:expr, $ok:expr) => {{
-        let ptr = $ptr; // avoid calling a function multiple times
-        if ptr.is_null() {
-            return Err(crate::NcError::with_msg(crate::c_api::NCRESULT_ERR, $msg));
+    (@fn $fn:expr, $ptr:expr, $msg:expr, $ok:expr) => {{
+        if $ptr.is_null() {
+            let mut err = crate::NcError::null_returned($fn, "");
+            err.msg = $msg.to_string();
+            return Err(err);
         } else {
             #[allow(unused_unsafe)]
             return Ok(unsafe { $ok });
         }
     }};
+    ($ptr:expr, $msg:expr, $ok:expr) => {{
+        crate::error::clear_errno();
+        let ptr = $ptr; // avoid calling a function multiple times
+        error_ref_mut![@fn crate::error::c_function_name(stringify!($ptr)), ptr, $msg, $ok];
+    }};
     ($ptr:expr, $msg:expr) => {{
+        crate::error::clear_errno();
         let ptr = $ptr;
-        error_ref_mut![ptr, $msg, unsafe { &mut *ptr }];
+        error_ref_mut![
+            @fn crate::error::c_function_name(stringify!($ptr)),
+            ptr,
+            $msg,
+            unsafe { &mut *ptr }
+        ];
     }};
     ($ptr:expr) => {{
+        crate::error::clear_errno();
         let ptr = $ptr;
-        error_ref_mut![ptr, "", unsafe { &mut *ptr }];
+        error_ref_mut![
+            @fn crate::error::c_function_name(stringify!($ptr)),
+            ptr,
+            "",
+            unsafe { &mut *ptr }
+        ];
     }};
 }
 
@@ -339,20 +382,25 @@ macro_rules! error_ref_mut {
 ///
 /// In other words:
 /// Returns Ok((&*`$str`).to_string()) if `!$str.is_null()`, otherwise returns
-/// Err([NcError]]::[new][NcError#method.new]([NCRESULT_ERR], `$msg`)).
+/// Err([NcError]]::[null_returned][NcError#method.null_returned](`$msg`)).
 ///
 /// `$msg` is optional. By default it will be an empty `&str` `""`.
 #[macro_export]
 #[doc(hidden)]
 macro_rules! error_str {
-    ($str:expr, $msg:expr) => {
-        if !$str.is_null() {
+    ($str:expr, $msg:expr) => {{
+        crate::error::clear_errno();
+        let str = $str; // avoid calling a function multiple times
+        if !str.is_null() {
             #[allow(unused_unsafe)]
-            return Ok(unsafe { crate::rstring!($str).to_string() });
+            return Ok(unsafe { crate::rstring!(str).to_string() });
         } else {
-            return Err(crate::NcError::with_msg(crate::c_api::NCRESULT_ERR, $msg));
+            let mut err =
+                crate::NcError::null_returned(crate::error::c_function_name(stringify!($str)), "");
+            err.msg = $msg.to_string();
+            return Err(err);
         }
-    };
+    }};
     ($str:expr) => {
         error_str![$str, ""];
     };
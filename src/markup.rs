@@ -0,0 +1,330 @@
+//! `NcMarkup`
+
+use crate::{
+    Nc, NcAlign, NcError, NcErrorKind, NcPlane, NcPlaneWriter, NcResult, NcRgb, NcStyle,
+    NcTextLayout,
+};
+
+/// The names of the colors, and their [`NcRgb`] value.
+const COLORS: [(&str, u32); 10] = [
+    ("black", 0x000000),
+    ("red", 0xff0000),
+    ("green", 0x00ff00),
+    ("yellow", 0xffff00),
+    ("blue", 0x0000ff),
+    ("magenta", 0xff00ff),
+    ("cyan", 0x00ffff),
+    ("white", 0xffffff),
+    ("grey", 0x808080),
+    ("gray", 0x808080),
+];
+
+/// The short names of the styles.
+const STYLE_ABBREVIATIONS: [(&str, NcStyle); 5] = [
+    ("b", NcStyle::Bold),
+    ("i", NcStyle::Italic),
+    ("u", NcStyle::Underline),
+    ("uc", NcStyle::Undercurl),
+    ("s", NcStyle::Struck),
+];
+
+/// A color of an [`NcSpan`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NcSpanColor {
+    /// The default color of the terminal.
+    Default,
+
+    /// An RGB color.
+    Rgb(NcRgb),
+}
+
+/// A fragment of text with the same styles and colors.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NcSpan {
+    /// The text.
+    pub text: String,
+
+    /// The styles, added to the ones of the plane.
+    pub style: NcStyle,
+
+    /// The foreground color, or `None` for the one of the plane.
+    pub fg: Option<NcSpanColor>,
+
+    /// The background color, or `None` for the one of the plane.
+    pub bg: Option<NcSpanColor>,
+}
+
+/// # Constructors
+impl NcSpan {
+    /// New span of `text`, without styles and with the colors of the plane.
+    pub fn new(text: &str) -> Self {
+        Self { text: text.to_owned(), style: NcStyle::None, fg: None, bg: None }
+    }
+}
+
+/// # Methods
+impl NcSpan {
+    /// Returns a writer over the `plane` with the styles and colors
+    /// of the span, that restores the previous ones when dropped.
+    pub fn writer<'plane>(&self, plane: &'plane mut NcPlane) -> NcPlaneWriter<'plane> {
+        let mut writer = plane.writer().on(self.style);
+        writer = match self.fg {
+            Some(NcSpanColor::Default) => writer.fg_default(),
+            Some(NcSpanColor::Rgb(rgb)) => writer.fg(rgb),
+            None => writer,
+        };
+        match self.bg {
+            Some(NcSpanColor::Default) => writer.bg_default(),
+            Some(NcSpanColor::Rgb(rgb)) => writer.bg(rgb),
+            None => writer,
+        }
+    }
+}
+
+/// Text with styles and colors, parsed from a markup.
+///
+/// The markup is text with tags between square brackets, which apply to the
+/// text until they are closed:
+///
+/// - `[…]` opens a tag with attributes separated by spaces:
+///   - styles: `bold`, `italic`, `underline`, `undercurl`, `struck`,
+///     (as in [`Nc::lex_styles`]) or `b`, `i`, `u`, `uc`, `s`.
+///   - foreground colors: `red`, `green`, `blue`, `yellow`, `magenta`,
+///     `cyan`, `black`, `white`, `grey`, `#rrggbb`, or `default`.
+///   - background colors: `on` followed by a color.
+/// - `[/]` closes the last open tag, and `[/…]` closes it by repeating it,
+///   or its first attribute.
+/// - `[[` is a literal `[`.
+///
+/// Tags can be nested, and the ones left open are closed at the end.
+///
+/// # Example
+/// ```no_run
+/// # use libnotcurses_sys::*;
+/// # fn main() -> NcResult<()> {
+/// # let nc = unsafe { Nc::new()? };
+/// # let plane = unsafe { nc.stdplane() };
+/// let markup = format!["[b red]Error:[/] file [u]{}[/u] missing", "a.txt"];
+/// plane.putmarkup(Some(0), NcAlign::Center, &markup)?;
+/// # unsafe { nc.stop()? };
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NcMarkup {
+    spans: Vec<NcSpan>,
+}
+
+/// # Constructors
+impl NcMarkup {
+    /// Parses a `markup`.
+    ///
+    /// Returns an error with the position of the problem if a tag isn't
+    /// closed, has unknown attributes, or closes a different tag.
+    pub fn parse(markup: &str) -> NcResult<Self> {
+        let mut spans: Vec<NcSpan> = vec![];
+        // the open tags, with the attributes in effect before them
+        let mut open: Vec<(&str, NcSpan)> = vec![];
+        let mut current = NcSpan::new("");
+
+        let mut rest = markup;
+        while let Some(start) = rest.find('[') {
+            current.text.push_str(&rest[..start]);
+            rest = &rest[start..];
+            if let Some(escaped) = rest.strip_prefix("[[") {
+                current.text.push('[');
+                rest = escaped;
+                continue;
+            }
+            let position = markup.len() - rest.len();
+            let end = rest
+                .find(']')
+                .ok_or_else(|| markup_error(position, "the tag is not closed"))?;
+            let tag = rest[1..end].trim();
+            rest = &rest[end + 1..];
+
+            let attrs = if let Some(closed) = tag.strip_prefix('/') {
+                match open.pop() {
+                    Some((opened, attrs)) if closes(closed.trim(), opened) => attrs,
+                    Some((opened, _)) => {
+                        let msg = format!["[{}] closes [{}]", tag, opened];
+                        return Err(markup_error(position, &msg));
+                    }
+                    None => return Err(markup_error(position, "there's no tag to close")),
+                }
+            } else {
+                let attrs = apply_tag(&current, tag).map_err(|msg| markup_error(position, &msg))?;
+                open.push((tag, NcSpan { text: String::new(), ..current.clone() }));
+                attrs
+            };
+            push_span(&mut spans, core::mem::replace(&mut current, attrs));
+        }
+        current.text.push_str(rest);
+        push_span(&mut spans, current);
+        Ok(Self { spans })
+    }
+
+    /// New markup from already styled `spans`.
+    pub fn from_spans(spans: Vec<NcSpan>) -> Self {
+        Self { spans }
+    }
+}
+
+/// # Methods
+impl NcMarkup {
+    /// Returns the styled spans.
+    pub fn spans(&self) -> &[NcSpan] {
+        &self.spans
+    }
+
+    /// Returns the text, without the tags.
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    /// Renders the text in the `plane`, starting at the row `y`,
+    /// or at the cursor row if `None`.
+    ///
+    /// The text is laid out in an [`NcTextLayout`] as wide as the plane,
+    /// with each line aligned horizontally with `align`, and drawn from
+    /// the column 0. See [`draw`][NcTextLayout#method.draw] for the details.
+    ///
+    /// Returns the number of rows written.
+    ///
+    /// *(No equivalent C style function)*
+    pub fn render(&self, plane: &mut NcPlane, y: Option<u32>, align: NcAlign) -> NcResult<u32> {
+        NcTextLayout::with_markup(self, plane.dim_x(), align).draw(plane, y, 0)
+    }
+}
+
+/// # `NcPlane` markup
+impl NcPlane {
+    /// Renders a text with styles and colors, described by a `markup`,
+    /// starting at the row `y`, or at the cursor row if `None`.
+    ///
+    /// See [`NcMarkup`] for the syntax, and
+    /// [`NcMarkup::render`][NcMarkup#method.render] for the details.
+    ///
+    /// Returns the number of rows written.
+    ///
+    /// *(No equivalent C style function)*
+    pub fn putmarkup(&mut self, y: Option<u32>, align: NcAlign, markup: &str) -> NcResult<u32> {
+        NcMarkup::parse(markup)?.render(self, y, align)
+    }
+}
+
+/// Returns an error about the markup at the byte `position`.
+fn markup_error(position: usize, msg: &str) -> NcError {
+    NcError::with_kind(
+        NcErrorKind::InvalidArgument,
+        &format!["NcMarkup: {} (at byte {})", msg, position],
+    )
+}
+
+/// Returns true if the `closed` name of a closing tag closes the `opened` tag.
+fn closes(closed: &str, opened: &str) -> bool {
+    closed.is_empty() || closed == opened || opened.split_whitespace().next() == Some(closed)
+}
+
+/// Adds a `span` to the `spans`, unless it's empty.
+fn push_span(spans: &mut Vec<NcSpan>, span: NcSpan) {
+    if !span.text.is_empty() {
+        spans.push(span);
+    }
+}
+
+/// Returns the attributes of `span`, changed by the attributes of a `tag`.
+fn apply_tag(span: &NcSpan, tag: &str) -> Result<NcSpan, String> {
+    let mut span = NcSpan { text: String::new(), ..span.clone() };
+    let mut attributes = tag.split_whitespace();
+    while let Some(attribute) = attributes.next() {
+        let attribute = attribute.to_lowercase();
+        if attribute == "on" {
+            let color = attributes.next().ok_or("`on` needs a color")?;
+            span.bg = Some(parse_color(color).ok_or_else(|| format!["unknown color `{}`", color])?);
+        } else if let Some(color) = parse_color(&attribute) {
+            span.fg = Some(color);
+        } else if let Some((_, style)) = STYLE_ABBREVIATIONS.iter().find(|(a, _)| *a == attribute) {
+            span.style |= *style;
+        } else {
+            span.style |= Nc::lex_styles(&attribute)
+                .map_err(|_| format!["unknown attribute `{}`", attribute])?;
+        }
+    }
+    Ok(span)
+}
+
+/// Returns the color named `name`, or written as `#rrggbb`.
+fn parse_color(name: &str) -> Option<NcSpanColor> {
+    let name = name.to_lowercase();
+    if name == "default" {
+        return Some(NcSpanColor::Default);
+    }
+    if let Some(hex) = name.strip_prefix('#') {
+        if hex.len() == 6 {
+            return u32::from_str_radix(hex, 16)
+                .ok()
+                .map(|rgb| NcSpanColor::Rgb(NcRgb(rgb)));
+        }
+        return None;
+    }
+    COLORS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, rgb)| NcSpanColor::Rgb(NcRgb(*rgb)))
+}
+
+mod std_impls {
+    use super::NcMarkup;
+    use crate::NcError;
+    use core::str::FromStr;
+
+    impl FromStr for NcMarkup {
+        type Err = NcError;
+
+        fn from_str(markup: &str) -> Result<Self, NcError> {
+            Self::parse(markup)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{NcMarkup, NcSpan, NcSpanColor};
+    use crate::{NcRgb, NcStyle};
+
+    #[test]
+    fn parse_tags() {
+        let markup =
+            NcMarkup::parse("[b red]Error:[/] file [u on #000080]a[[1][/u] missing").unwrap();
+        let spans = markup.spans();
+        assert_eq!["Error: file a[1] missing", markup.text()];
+        assert_eq![4, spans.len()];
+        assert_eq![NcStyle::Bold, spans[0].style];
+        assert_eq![Some(NcSpanColor::Rgb(NcRgb(0xff0000))), spans[0].fg];
+        assert_eq![NcSpan::new(" file "), spans[1]];
+        assert_eq![NcStyle::Underline, spans[2].style];
+        assert_eq![Some(NcSpanColor::Rgb(NcRgb(0x000080))), spans[2].bg];
+        assert_eq![None, spans[2].fg];
+        assert_eq![NcSpan::new(" missing"), spans[3]];
+    }
+
+    #[test]
+    fn parse_nested_tags() {
+        let markup = NcMarkup::parse("[italic]a[s default]b[/s default]c").unwrap();
+        let spans = markup.spans();
+        assert_eq![NcStyle::Italic | NcStyle::Struck, spans[1].style];
+        assert_eq![Some(NcSpanColor::Default), spans[1].fg];
+        assert_eq![NcStyle::Italic, spans[2].style];
+        assert_eq![None, spans[2].fg];
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert![NcMarkup::parse("[b unclosed").is_err()];
+        assert![NcMarkup::parse("[blink]a").is_err()];
+        assert![NcMarkup::parse("[b]a[/i]").is_err()];
+        assert![NcMarkup::parse("a[/]").is_err()];
+        assert![NcMarkup::parse("[on]a").is_err()];
+    }
+}
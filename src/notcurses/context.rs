@@ -0,0 +1,280 @@
+//! `NcContext`
+
+use core::{
+    cell::Cell,
+    ptr::{null, null_mut, NonNull},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{
+    c_api, error, error::clear_errno, panic_hook, plane::user_data::free_nc_data, Nc, NcError,
+    NcErrorKind, NcEvents, NcFlags, NcInput, NcLogLevel, NcOptions, NcPlaneHandle, NcReceived,
+    NcResult, NcTime,
+};
+
+/// Whether there's currently an [`NcContext`] alive in this process.
+static CONTEXT_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// An owned notcurses context, that stops itself when dropped.
+///
+/// It dereferences to [`Nc`], so all of its methods are available.
+///
+/// Planes can be safely accessed through the [`NcPlaneHandle`]s returned by
+/// [`stdplane_handle`][NcContext#method.stdplane_handle] and
+/// [`new_pile`][NcContext#method.new_pile], which borrow the context.
+///
+/// [`NcPlaneHandle`]: crate::NcPlaneHandle
+///
+/// Only one `NcContext` can be alive at a time. Trying to create a new one
+/// while another one exists returns an error instead of initializing
+/// notcurses twice.
+///
+/// Note that this guard doesn't know about the contexts created with the
+/// unsafe [`Nc`] constructors, which are still responsible for upholding
+/// their own contracts.
+///
+/// # Example
+/// ```no_run
+/// use libnotcurses_sys::*;
+///
+/// # fn main() -> NcResult<()> {
+/// let nc = NcContext::new_cli()?;
+/// let mut stdplane = nc.stdplane_handle()?;
+/// stdplane.putstrln("hello world")?;
+/// nc.render()?;
+/// drop(stdplane);
+/// nc.stop()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct NcContext {
+    nc: NonNull<Nc>,
+    panic_hook: bool,
+    // whether there's a handle to the standard plane alive
+    pub(crate) stdplane_lent: Cell<bool>,
+}
+
+/// # `NcContext` Constructors and destructors
+impl NcContext {
+    /// New notcurses context (without banners).
+    pub fn new() -> NcResult<Self> {
+        Self::with_flags(NcFlags::SuppressBanners)
+    }
+
+    /// New notcurses context in CLI mode.
+    ///
+    /// See [`Nc::new_cli`][Nc#method.new_cli] for the list of flags.
+    pub fn new_cli() -> NcResult<Self> {
+        Self::with_flags(NcFlags::CliMode)
+    }
+
+    /// New notcurses context, with banners.
+    pub fn with_banners() -> NcResult<Self> {
+        Self::with_flags(NcFlags::None)
+    }
+
+    /// New notcurses context, expects [`NcFlags`].
+    pub fn with_flags(flags: NcFlags) -> NcResult<Self> {
+        Self::with_options(NcOptions::with_flags(flags))
+    }
+
+    /// New notcurses context, expects [`NcLogLevel`] and flags.
+    pub fn with_debug(loglevel: NcLogLevel, flags: NcFlags) -> NcResult<Self> {
+        Self::with_options(NcOptions::with_all_options(loglevel, 0, 0, 0, 0, flags))
+    }
+
+    /// New notcurses context, expects [`NcOptions`].
+    ///
+    /// Returns an error if there's already another `NcContext` alive.
+    ///
+    /// *C style function: [notcurses_init()][c_api::notcurses_init].*
+    pub fn with_options(options: NcOptions) -> NcResult<Self> {
+        Self::init(options, null_mut(), false)
+    }
+
+    /// New notcurses context, expects [`NcOptions`], and installs a panic hook
+    /// that restores the terminal before the panic message gets printed.
+    ///
+    /// When panicking while this context is alive, the hook leaves the
+    /// alternate screen and restores the terminal modes from before the
+    /// context was created, so that the input is echoed and line buffered
+    /// again, and then delegates to the previous panic hook, so that the
+    /// message and backtrace are readable. Notcurses is stopped afterwards,
+    /// when the context is dropped while unwinding.
+    ///
+    /// With `panic = "abort"` the context is never dropped, so whatever else
+    /// notcurses changed, like the mouse tracking or the cursor visibility,
+    /// is left as it was.
+    ///
+    /// *C style function: [notcurses_init()][c_api::notcurses_init].*
+    pub fn with_panic_hook(options: NcOptions) -> NcResult<Self> {
+        Self::init(options, null_mut(), true)
+    }
+
+    /// New notcurses context writing to `fp`, or to stdout if it's null.
+    pub(crate) fn init(
+        options: NcOptions,
+        fp: *mut c_api::ffi::FILE,
+        panic_hook: bool,
+    ) -> NcResult<Self> {
+        if CONTEXT_ACTIVE
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return Err(NcError::with_kind(
+                NcErrorKind::InvalidArgument,
+                "NcContext.with_options(): another NcContext is already active",
+            ));
+        }
+        let modes = panic_hook.then(panic_hook::TermModes::save);
+        clear_errno();
+        let res = unsafe { c_api::notcurses_init(&options, fp) };
+        match NonNull::new(res) {
+            Some(nc) => {
+                if let Some(modes) = modes {
+                    panic_hook::register_nc(nc.as_ptr(), modes);
+                }
+                Ok(Self { nc, panic_hook, stdplane_lent: Cell::new(false) })
+            }
+            None => {
+                CONTEXT_ACTIVE.store(false, Ordering::Release);
+                Err(NcError::null_returned(
+                    "notcurses_init",
+                    "NcContext.with_options()",
+                ))
+            }
+        }
+    }
+
+    /// Stops the notcurses context, consuming it.
+    ///
+    /// Since it takes `self` by value it can only be called once. Dropping
+    /// the context without calling this method stops it too, but then any
+    /// error gets ignored.
+    ///
+    /// *C style function: [notcurses_stop()][c_api::notcurses_stop].*
+    pub fn stop(self) -> NcResult<()> {
+        let nc = self.nc.as_ptr();
+        free_nc_data(nc, null());
+        if self.panic_hook {
+            panic_hook::unregister_nc(nc);
+        }
+        core::mem::forget(self);
+        clear_errno();
+        let res = unsafe { c_api::notcurses_stop(nc) };
+        CONTEXT_ACTIVE.store(false, Ordering::Release);
+        error![@fn "notcurses_stop", res, "NcContext.stop()"]
+    }
+}
+
+/// # `NcContext` methods
+impl NcContext {
+    /// Returns the raw pointer to the wrapped [`Nc`].
+    ///
+    /// The pointer is valid for as long as this context is alive.
+    pub fn as_ptr(&self) -> *mut Nc {
+        self.nc.as_ptr()
+    }
+
+    /// Returns true if there's currently an `NcContext` alive.
+    pub fn is_active() -> bool {
+        CONTEXT_ACTIVE.load(Ordering::Acquire)
+    }
+
+    // The following methods only need a shared reference, so that they can be
+    // called while there are plane handles borrowing this context. Notcurses
+    // synchronizes these operations internally, and they call it through the
+    // raw pointer, without ever forming an exclusive reference to the `Nc`.
+
+    /// Renders and rasterizes the standard pile.
+    ///
+    /// See [`Nc::render`][Nc#method.render].
+    pub fn render(&self) -> NcResult<()> {
+        unsafe {
+            let stdplane = c_api::notcurses_stdplane(self.as_ptr());
+            NcPlaneHandle::render_pile_ptr(stdplane, "NcContext.render()")
+        }
+    }
+
+    /// Gets an [`NcReceived`] from input.
+    ///
+    /// See [`Nc::get`][Nc#method.get].
+    pub fn get(&self, time: Option<NcTime>, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        unsafe { Nc::get_ptr(self.as_ptr(), time, input) }
+    }
+
+    /// Returns an iterator over the input [`NcEvent`]s.
+    ///
+    /// See [`Nc::events`][Nc#method.events].
+    ///
+    /// [`NcEvent`]: crate::NcEvent
+    pub fn events(&self, timeout: Option<NcTime>) -> NcEvents<&NcContext> {
+        NcEvents::new(self, timeout)
+    }
+
+    /// Returns an asynchronous stream of the input [`NcEvent`]s.
+    ///
+    /// See [`Nc::event_stream`][Nc#method.event_stream].
+    ///
+    /// *Requires the `async` feature.*
+    ///
+    /// [`NcEvent`]: crate::NcEvent
+    #[cfg(all(feature = "async", unix))]
+    pub fn event_stream(&self) -> NcResult<crate::NcEventStream<&NcContext>> {
+        unsafe {
+            let fd = crate::input::stream::nc_input_fd(self.as_ptr())?;
+            crate::NcEventStream::new(self, fd)
+        }
+    }
+
+    /// Reads input blocking until an event is processed or a signal is received.
+    ///
+    /// See [`Nc::get_blocking`][Nc#method.get_blocking].
+    pub fn get_blocking(&self, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        unsafe { Nc::get_ptr(self.as_ptr(), None, input) }
+    }
+
+    /// Reads input without blocking.
+    ///
+    /// See [`Nc::get_nblock`][Nc#method.get_nblock].
+    pub fn get_nblock(&self, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        unsafe { Nc::get_ptr(self.as_ptr(), Some(NcTime::new(0, 0)), input) }
+    }
+}
+
+mod std_impls {
+    use super::{NcContext, CONTEXT_ACTIVE};
+    use crate::{c_api, panic_hook, plane::user_data::free_nc_data, Nc};
+    use core::{
+        ops::{Deref, DerefMut},
+        ptr::null,
+        sync::atomic::Ordering,
+    };
+
+    impl Deref for NcContext {
+        type Target = Nc;
+
+        fn deref(&self) -> &Nc {
+            unsafe { self.nc.as_ref() }
+        }
+    }
+
+    impl DerefMut for NcContext {
+        fn deref_mut(&mut self) -> &mut Nc {
+            unsafe { self.nc.as_mut() }
+        }
+    }
+
+    impl Drop for NcContext {
+        fn drop(&mut self) {
+            let nc = self.nc.as_ptr();
+            free_nc_data(nc, null());
+            if self.panic_hook {
+                panic_hook::unregister_nc(nc);
+            }
+            let _ = unsafe { c_api::notcurses_stop(nc) };
+            CONTEXT_ACTIVE.store(false, Ordering::Release);
+        }
+    }
+}
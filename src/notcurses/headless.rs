@@ -0,0 +1,324 @@
+//! `NcHeadless`
+
+use core::{convert::TryFrom, ptr::null_mut};
+use std::{
+    ffi::CString,
+    fs::File,
+    io::{Read, Write},
+    os::unix::io::FromRawFd,
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    c_api::libc, grid::Emulator, NcContext, NcError, NcErrorKind, NcFile, NcFlags, NcGrid,
+    NcOptions, NcResult,
+};
+
+/// The terminal type used by default.
+const DEFAULT_TERMTYPE: &str = "xterm-256color";
+
+/// The Primary Device Attributes query, which notcurses sends last
+/// and waits for its reply.
+const QUERY_DA1: &[u8] = b"\x1b[c";
+/// The reply to [`QUERY_DA1`], identifying a VT220.
+const REPLY_DA1: &[u8] = b"\x1b[?62c";
+/// The Cursor Position Report query.
+const QUERY_CPR: &[u8] = b"\x1b[6n";
+/// The reply to [`QUERY_CPR`], with the cursor at the origin.
+const REPLY_CPR: &[u8] = b"\x1b[1;1R";
+
+/// A notcurses context rendering into a pseudo-terminal,
+/// without needing a real terminal.
+///
+/// It allocates a local pty with fixed dimensions, and starts notcurses on it
+/// with a chosen terminal type. A background thread plays the terminal:
+/// it collects everything notcurses writes, keeps track of the resulting
+/// [`screen`][NcHeadless#method.screen], and answers the queries notcurses
+/// sends while starting up.
+///
+/// This allows running rendering tests in environments without a TTY,
+/// like CI. Since it wraps an [`NcContext`], only one can be alive at a time.
+///
+/// It dereferences to [`NcContext`], so all of its methods are available.
+///
+/// # Example
+/// ```no_run
+/// use libnotcurses_sys::*;
+///
+/// # fn main() -> NcResult<()> {
+/// let nc = NcHeadless::new(24, 80)?;
+/// assert_eq![(24, 80), nc.term_dim_yx()];
+/// let mut stdplane = nc.stdplane_handle()?;
+/// stdplane.putstr("hello world")?;
+/// nc.render()?;
+/// let row = nc.screen().row_text(0).unwrap_or_default();
+/// assert_eq!["hello world", row.trim_end()];
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct NcHeadless {
+    context: Option<NcContext>,
+    master: File,
+    slave: Option<NcFile>,
+    terminal: Arc<Mutex<Terminal>>,
+    terminal_thread: Option<JoinHandle<()>>,
+}
+
+/// What the terminal has received.
+#[derive(Debug)]
+struct Terminal {
+    output: Vec<u8>,
+    screen: Emulator,
+}
+
+/// # `NcHeadless` Constructors
+impl NcHeadless {
+    /// New headless notcurses context of `rows` × `cols`,
+    /// with the `xterm-256color` terminal type.
+    pub fn new(rows: u32, cols: u32) -> NcResult<Self> {
+        Self::with_termtype(rows, cols, DEFAULT_TERMTYPE)
+    }
+
+    /// New headless notcurses context of `rows` × `cols`,
+    /// with the provided terminal type, e.g. `"xterm-256color"`.
+    pub fn with_termtype(rows: u32, cols: u32, termtype: &str) -> NcResult<Self> {
+        let flags = NcFlags::SuppressBanners
+            | NcFlags::DrainInput
+            | NcFlags::NoQuitSigHandlers
+            | NcFlags::NoWinchSigHandler;
+        Self::with_options(rows, cols, termtype, NcOptions::with_flags(flags))
+    }
+
+    /// New headless notcurses context of `rows` × `cols`,
+    /// with the provided terminal type and [`NcOptions`].
+    ///
+    /// The `termtype` of the options is overriden.
+    ///
+    /// *C style function: [notcurses_init()][crate::c_api::notcurses_init].*
+    pub fn with_options(
+        rows: u32,
+        cols: u32,
+        termtype: &str,
+        mut options: NcOptions,
+    ) -> NcResult<Self> {
+        let termtype = CString::new(termtype).map_err(|_| {
+            NcError::with_kind(
+                NcErrorKind::InvalidArgument,
+                "NcHeadless.with_options(): termtype contains a nul byte",
+            )
+        })?;
+        let mut size = winsize(rows, cols)?;
+
+        let (mut master_fd, mut slave_fd) = (-1, -1);
+        let res = unsafe {
+            libc::openpty(
+                &mut master_fd,
+                &mut slave_fd,
+                null_mut(),
+                null_mut(),
+                &mut size,
+            )
+        };
+        if res != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        let master = unsafe { File::from_raw_fd(master_fd) };
+        let slave_ptr = unsafe { libc::fdopen(slave_fd, b"w\0".as_ptr() as *const libc::c_char) };
+        if slave_ptr.is_null() {
+            let error = std::io::Error::last_os_error();
+            unsafe { libc::close(slave_fd) };
+            return Err(error.into());
+        }
+        let slave = unsafe { NcFile::from_libc(slave_ptr) };
+
+        let terminal = Arc::new(Mutex::new(Terminal {
+            output: Vec::new(),
+            screen: Emulator::new(rows, cols),
+        }));
+        let terminal_thread = {
+            let reader = master.try_clone()?;
+            let writer = master.try_clone()?;
+            let terminal = Arc::clone(&terminal);
+            thread::spawn(move || play_terminal(reader, writer, terminal))
+        };
+
+        options.termtype = termtype.as_ptr();
+        let context = NcContext::init(options, slave.as_nc_ptr(), false)?;
+        Ok(Self {
+            context: Some(context),
+            master,
+            slave: Some(slave),
+            terminal,
+            terminal_thread: Some(terminal_thread),
+        })
+    }
+}
+
+/// # `NcHeadless` methods
+impl NcHeadless {
+    /// Returns a reference to the wrapped [`NcContext`].
+    pub fn context(&self) -> &NcContext {
+        self.context.as_ref().expect("NcHeadless context")
+    }
+
+    /// Returns a mutable reference to the wrapped [`NcContext`].
+    pub fn context_mut(&mut self) -> &mut NcContext {
+        self.context.as_mut().expect("NcHeadless context")
+    }
+
+    /// Resizes the pseudo-terminal to `rows` × `cols`,
+    /// and makes notcurses pick up the new size.
+    ///
+    /// Returns the new dimensions, as seen by notcurses.
+    ///
+    /// *C style function: [notcurses_refresh()][crate::c_api::notcurses_refresh].*
+    pub fn resize(&mut self, rows: u32, cols: u32) -> NcResult<(u32, u32)> {
+        use std::os::unix::io::AsRawFd;
+        let size = winsize(rows, cols)?;
+        if let Ok(mut terminal) = self.terminal.lock() {
+            terminal.screen.resize(rows, cols);
+        }
+        let res = unsafe { libc::ioctl(self.master.as_raw_fd(), libc::TIOCSWINSZ, &size) };
+        if res != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        self.context_mut().refresh()
+    }
+
+    /// Returns a copy of everything notcurses has written to the terminal.
+    pub fn output(&self) -> Vec<u8> {
+        self.terminal
+            .lock()
+            .map(|t| t.output.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns everything notcurses has written to the terminal,
+    /// emptying the collected output.
+    pub fn take_output(&self) -> Vec<u8> {
+        self.terminal
+            .lock()
+            .map(|mut t| core::mem::take(&mut t.output))
+            .unwrap_or_default()
+    }
+
+    /// Returns what the terminal currently shows.
+    ///
+    /// It's the result of interpreting everything notcurses has written
+    /// to the terminal, so it's only updated by rasterizing, e.g. with
+    /// [`render`][NcContext#method.render].
+    pub fn screen(&self) -> NcGrid {
+        self.terminal
+            .lock()
+            .map(|t| t.screen.grid().clone())
+            .unwrap_or_else(|e| e.into_inner().screen.grid().clone())
+    }
+}
+
+/// Returns the `winsize` for `rows` × `cols`.
+fn winsize(rows: u32, cols: u32) -> NcResult<libc::winsize> {
+    match (u16::try_from(rows), u16::try_from(cols)) {
+        (Ok(ws_row), Ok(ws_col)) if ws_row > 0 && ws_col > 0 => {
+            Ok(libc::winsize { ws_row, ws_col, ws_xpixel: 0, ws_ypixel: 0 })
+        }
+        _ => Err(NcError::with_kind(
+            NcErrorKind::InvalidArgument,
+            &format!["NcHeadless: invalid dimensions {}×{}", rows, cols],
+        )),
+    }
+}
+
+/// Reads everything written to the pty and answers the queries,
+/// until the slave side gets closed.
+fn play_terminal(mut reader: File, mut writer: File, terminal: Arc<Mutex<Terminal>>) {
+    let mut buf = [0; 4096];
+    // the bytes not yet scanned for queries
+    let mut pending = Vec::new();
+    loop {
+        let read = match reader.read(&mut buf) {
+            Ok(0) | Err(_) => return,
+            Ok(n) => n,
+        };
+        if let Ok(mut terminal) = terminal.lock() {
+            terminal.output.extend_from_slice(&buf[..read]);
+            terminal.screen.feed(&buf[..read]);
+        }
+        pending.extend_from_slice(&buf[..read]);
+        let reply = reply_queries(&mut pending);
+        if !reply.is_empty() && writer.write_all(&reply).is_err() {
+            return;
+        }
+    }
+}
+
+/// Returns the replies to the queries in `pending`, keeping only
+/// a possibly incomplete query at its end.
+fn reply_queries(pending: &mut Vec<u8>) -> Vec<u8> {
+    let mut reply = Vec::new();
+    let mut i = 0;
+    while i < pending.len() {
+        let rest = &pending[i..];
+        if rest.starts_with(QUERY_DA1) {
+            reply.extend_from_slice(REPLY_DA1);
+            i += QUERY_DA1.len();
+        } else if rest.starts_with(QUERY_CPR) {
+            reply.extend_from_slice(REPLY_CPR);
+            i += QUERY_CPR.len();
+        } else if QUERY_DA1.starts_with(rest) || QUERY_CPR.starts_with(rest) {
+            break;
+        } else {
+            i += 1;
+        }
+    }
+    pending.drain(..i);
+    reply
+}
+
+mod std_impls {
+    use super::NcHeadless;
+    use crate::NcContext;
+    use core::ops::{Deref, DerefMut};
+
+    impl Deref for NcHeadless {
+        type Target = NcContext;
+
+        fn deref(&self) -> &NcContext {
+            self.context()
+        }
+    }
+
+    impl DerefMut for NcHeadless {
+        fn deref_mut(&mut self) -> &mut NcContext {
+            self.context_mut()
+        }
+    }
+
+    impl Drop for NcHeadless {
+        fn drop(&mut self) {
+            // stop notcurses before closing the terminal it writes to
+            drop(self.context.take());
+            drop(self.slave.take());
+            if let Some(terminal) = self.terminal_thread.take() {
+                let _ = terminal.join();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::reply_queries;
+
+    #[test]
+    fn reply_split_queries() {
+        let mut pending = b"\x1b[?1049h\x1b[6n\x1b[>0q\x1b".to_vec();
+        assert_eq![b"\x1b[1;1R".to_vec(), reply_queries(&mut pending)];
+        assert_eq![b"\x1b".to_vec(), pending];
+
+        pending.extend_from_slice(b"[c");
+        assert_eq![b"\x1b[?62c".to_vec(), reply_queries(&mut pending)];
+        assert![pending.is_empty()];
+    }
+}
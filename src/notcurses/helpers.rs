@@ -8,3 +8,21 @@ pub(crate) unsafe fn notcurses_init_test<'a>() -> &'a mut Nc {
         core::ptr::null_mut(),
     )
 }
+
+/// Helper function for running a test on a new pile of `rows` × `cols`,
+/// in a headless context, so that it doesn't need a terminal.
+///
+/// The family of the pile is destroyed afterwards.
+#[cfg(all(test, unix))]
+pub(crate) fn with_test_pile<F>(rows: u32, cols: u32, test: F) -> crate::NcResult<()>
+where
+    F: for<'nc> FnOnce(
+        &'nc crate::NcHeadless,
+        &mut crate::NcPlaneHandle<'nc>,
+    ) -> crate::NcResult<()>,
+{
+    let nc = crate::NcHeadless::new(24, 80)?;
+    let mut pile = nc.new_pile_sized(0, 0, rows, cols)?;
+    test(&nc, &mut pile)?;
+    pile.family_destroy()
+}
@@ -4,8 +4,12 @@ use core::ptr::{null, null_mut};
 
 use crate::{
     c_api::{self, notcurses_init},
-    cstring, error, error_ref_mut, rstring, rstring_free, Nc, NcAlign, NcBlitter, NcCapabilities,
-    NcChannels, NcError, NcFile, NcFlags, NcInput, NcLogLevel, NcMiceEvents, NcOptions,
+    cstring, error,
+    error::clear_errno,
+    error_ref_mut,
+    plane::user_data::free_nc_data,
+    rstring, rstring_free, Nc, NcAlign, NcBlitter, NcCapabilities, NcChannels, NcError,
+    NcErrorKind, NcEvent, NcEvents, NcFile, NcFlags, NcInput, NcLogLevel, NcMiceEvents, NcOptions,
     NcPixelImpl, NcPlane, NcReceived, NcResult, NcRgb, NcScale, NcStats, NcStyle, NcTime, NcVisual,
     NcVisualGeometry, NcVisualOptions,
 };
@@ -158,6 +162,7 @@ impl Nc {
     ///
     /// *C style function: [notcurses_stop()][c_api::notcurses_stop].*
     pub unsafe fn stop(&mut self) -> NcResult<()> {
+        free_nc_data(self, null());
         error![c_api::notcurses_stop(self)]
     }
 }
@@ -174,6 +179,7 @@ impl Nc {
     pub fn align(availcols: u32, align: NcAlign, cols: u32) -> NcResult<u32> {
         let res = c_api::notcurses_align(availcols, align, cols);
         error![
+            @fn "notcurses_align",
             res,
             &format!("NcPlane.valign({:?}, {})", align, cols),
             res as u32
@@ -423,8 +429,11 @@ impl Nc {
 
     /// Destroys all [`NcPlane`]s other than the stdplane.
     ///
+    /// Their [user data][NcPlane#method.set_user_data] is dropped.
+    ///
     /// *C style function: [notcurses_drop_planes()][c_api::notcurses_drop_planes].*
     pub fn drop_planes(&mut self) {
+        free_nc_data(self, unsafe { c_api::notcurses_stdplane_const(self) });
         unsafe {
             c_api::notcurses_drop_planes(self);
         }
@@ -443,6 +452,19 @@ impl Nc {
         &mut self,
         time: Option<NcTime>,
         input: Option<&mut NcInput>,
+    ) -> NcResult<NcReceived> {
+        unsafe { Nc::get_ptr(self, time, input) }
+    }
+
+    /// Like [`get`][Nc#method.get], but through a raw pointer, so that it
+    /// can be called without an exclusive reference to the context.
+    ///
+    /// # Safety
+    /// `nc` must point to a valid context.
+    pub(crate) unsafe fn get_ptr(
+        nc: *mut Nc,
+        time: Option<NcTime>,
+        input: Option<&mut NcInput>,
     ) -> NcResult<NcReceived> {
         let ntime;
         if let Some(time) = time {
@@ -458,14 +480,45 @@ impl Nc {
             ninput = null_mut();
         }
 
-        let res = unsafe { c_api::notcurses_get(self, ntime, ninput) };
+        clear_errno();
+        let res = c_api::notcurses_get(nc, ntime, ninput);
         if res == c_api::NCRESULT_ERR as u32 {
-            Err(NcError::new_msg(&format!["Nc.get({:?})", time]))
+            Err(NcError::negative_return(
+                "notcurses_get",
+                c_api::NCRESULT_ERR,
+                &format!["Nc.get({:?})", time],
+            ))
         } else {
             Ok(NcReceived::from(res))
         }
     }
 
+    /// Returns an iterator over the input [`NcEvent`]s.
+    ///
+    /// Provide `None` in `timeout` to block at length for each event, and
+    /// otherwise `Some(`[`NcTime`]`)` to bound blocking, in which case the
+    /// iteration ends when no event arrives in time.
+    ///
+    /// *(No equivalent C style function)*
+    pub fn events(&mut self, timeout: Option<NcTime>) -> NcEvents<&mut Nc> {
+        NcEvents::new(self, timeout)
+    }
+
+    /// Returns an asynchronous stream of the input [`NcEvent`]s.
+    ///
+    /// See [`NcEventStream`][crate::NcEventStream] for details.
+    ///
+    /// *Requires the `async` feature.*
+    ///
+    /// *(No equivalent C style function)*
+    #[cfg(all(feature = "async", unix))]
+    pub fn event_stream(&mut self) -> NcResult<crate::NcEventStream<&mut Nc>> {
+        unsafe {
+            let fd = crate::input::stream::nc_input_fd(self)?;
+            crate::NcEventStream::new(self, fd)
+        }
+    }
+
     /// Reads input blocking until an event is processed or a signal is received.
     ///
     /// Will optionally write the event details in `input`.
@@ -474,9 +527,14 @@ impl Nc {
     ///
     /// *C style function: [notcurses_get_blocking()][c_api::notcurses_get_blocking].*
     pub fn get_blocking(&mut self, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        clear_errno();
         let res = c_api::notcurses_get_blocking(self, input);
         if res == c_api::NCRESULT_ERR {
-            Err(NcError::new_msg("Nc.get_blocking()"))
+            Err(NcError::negative_return(
+                "notcurses_get_blocking",
+                res,
+                "Nc.get_blocking()",
+            ))
         } else {
             Ok(NcReceived::from(res as u32))
         }
@@ -490,9 +548,14 @@ impl Nc {
     ///
     /// *C style function: [notcurses_get_nblock()][c_api::notcurses_get_nblock].*
     pub fn get_nblock(&mut self, input: Option<&mut NcInput>) -> NcResult<NcReceived> {
+        clear_errno();
         let res = c_api::notcurses_get_nblock(self, input);
         if res == c_api::NCRESULT_ERR {
-            Err(NcError::new_msg("Nc.get_nblock()"))
+            Err(NcError::negative_return(
+                "notcurses_get_nblock",
+                res,
+                "Nc.get_nblock()",
+            ))
         } else {
             Ok(NcReceived::from(res as u32))
         }
@@ -517,8 +580,9 @@ impl Nc {
         }
         let nivec = ni.as_mut_ptr() as *mut NcInput;
 
+        clear_errno();
         let res = unsafe { c_api::notcurses_getvec(self, ntime, nivec, vcount as i32) };
-        error![res, "", res as u32]
+        error![@fn "notcurses_getvec", res, "", res as u32]
     }
 
     /// Gets a file descriptor suitable for input event poll()ing.
@@ -530,8 +594,10 @@ impl Nc {
     /// with stdin (but it might be!).
     ///
     /// *C style function: [notcurses_inputready_fd()][c_api::notcurses_inputready_fd].*
-    pub fn inputready_fd(&mut self) -> NcResult<()> {
-        error![unsafe { c_api::notcurses_inputready_fd(self) }]
+    pub fn inputready_fd(&mut self) -> NcResult<i32> {
+        clear_errno();
+        let res = unsafe { c_api::notcurses_inputready_fd(self) };
+        error![@fn "notcurses_inputready_fd", res, "Nc.inputready_fd()", res]
     }
 
     /// Returns an [`NcBlitter`] from a string representation.
@@ -655,7 +721,11 @@ impl Nc {
     pub fn palette_size(&self) -> NcResult<u32> {
         let res = unsafe { c_api::notcurses_palette_size(self) };
         if res == 1 {
-            return Err(NcError::with_msg(1, "No color support ← Nc.palette_size()"));
+            return Err(NcError::with_kind(
+                NcErrorKind::Unsupported,
+                "Nc.palette_size(): no color support",
+            )
+            .with_function("notcurses_palette_size"));
         }
         Ok(res)
     }
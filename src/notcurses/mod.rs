@@ -70,6 +70,9 @@
 //~r   notcurses_stddim_yx_const     //
 // rm  notcurses_term_dim_yx
 
+mod context;
+#[cfg(unix)]
+mod headless;
 mod methods;
 
 pub(crate) mod helpers;
@@ -78,6 +81,10 @@ pub(crate) mod reimplemented;
 #[cfg(test)]
 mod test;
 
+pub use context::NcContext;
+#[cfg(unix)]
+pub use headless::NcHeadless;
+
 /// Notcurses state for a given terminal, composed of [`NcPlane`]s.
 ///
 /// It's built atop the terminfo abstraction layer to provide reasonably
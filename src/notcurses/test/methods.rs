@@ -1,4 +1,47 @@
 //! Test `Notcurses` methods and associated functions.
 
-// use crate::Notcurses;
-// use serial_test::serial;
+use serial_test::serial;
+
+use crate::NcContext;
+
+#[test]
+#[serial]
+#[cfg(unix)]
+fn nccontext_single_instance() {
+    for _ in 0..2 {
+        let nc = crate::NcHeadless::new(4, 20).expect("headless context");
+        assert![NcContext::is_active()];
+        assert![NcContext::new().is_err()];
+        drop(nc);
+        assert![!NcContext::is_active()];
+    }
+}
+
+#[test]
+#[serial]
+#[cfg(unix)]
+fn ncheadless_dimensions() {
+    let mut nc = crate::NcHeadless::new(20, 60).expect("headless context");
+    assert![NcContext::is_active()];
+    assert_eq![(20, 60), nc.term_dim_yx()];
+    assert_eq![(30, 100), nc.resize(30, 100).expect("resize")];
+    drop(nc);
+    assert![!NcContext::is_active()];
+}
+
+#[test]
+#[serial]
+#[cfg(unix)]
+fn ncheadless_screen() {
+    let nc = crate::NcHeadless::new(4, 20).expect("headless context");
+    let mut stdplane = nc.stdplane_handle().expect("stdplane");
+    stdplane
+        .putstr_yx(Some(1), Some(2), "hello")
+        .expect("putstr");
+    nc.render().expect("render");
+    drop(stdplane);
+    assert_eq![
+        Some("  hello".to_owned()),
+        nc.screen().row_text(1).map(|r| r.trim_end().to_owned())
+    ];
+}
@@ -0,0 +1,171 @@
+//! A panic hook that restores the terminal before printing the panic.
+//!
+//! It's installed by [`NcContext::with_panic_hook`] and
+//! [`NcDirectContext::with_panic_hook`].
+//!
+//! The hook runs before unwinding, while the contexts and everything that
+//! borrows them are still alive, so it only restores the terminal: the modes
+//! saved before initializing the context, and what can be undone through the
+//! context without stopping it. Stopping the contexts is left to their `Drop`,
+//! which doesn't run with `panic = "abort"`.
+//!
+//! [`NcContext::with_panic_hook`]: crate::NcContext#method.with_panic_hook
+//! [`NcDirectContext::with_panic_hook`]: crate::NcDirectContext#method.with_panic_hook
+
+use core::{
+    ptr::null_mut,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+use std::{
+    panic,
+    sync::{Mutex, MutexGuard, Once},
+};
+
+use crate::{c_api, Nc, NcDirect};
+
+/// The terminal modes saved before initializing a context, which notcurses
+/// changes, e.g. disabling the echo and the line buffering of the input.
+#[derive(Clone, Copy)]
+pub(crate) struct TermModes {
+    #[cfg(unix)]
+    saved: Option<(c_api::libc::c_int, c_api::libc::termios)>,
+}
+
+impl TermModes {
+    /// Saves the modes of the first standard stream that's a terminal.
+    #[cfg(unix)]
+    pub(crate) fn save() -> Self {
+        use c_api::libc;
+        let saved = [libc::STDIN_FILENO, libc::STDOUT_FILENO, libc::STDERR_FILENO]
+            .iter()
+            .find_map(|&fd| {
+                let mut termios = core::mem::MaybeUninit::uninit();
+                // SAFETY: termios is initialized when tcgetattr succeeds
+                unsafe {
+                    (libc::tcgetattr(fd, termios.as_mut_ptr()) == 0)
+                        .then(|| (fd, termios.assume_init()))
+                }
+            });
+        Self { saved }
+    }
+
+    /// Saves the modes of the first standard stream that's a terminal.
+    #[cfg(not(unix))]
+    pub(crate) fn save() -> Self {
+        Self {}
+    }
+
+    /// Restores the saved modes, if any.
+    fn restore(&self) {
+        #[cfg(unix)]
+        if let Some((fd, termios)) = &self.saved {
+            unsafe {
+                c_api::libc::tcsetattr(*fd, c_api::libc::TCSANOW, termios);
+            }
+        }
+    }
+}
+
+/// The contexts whose terminal is restored when panicking,
+/// with the modes saved before initializing them.
+struct Registered {
+    nc: *mut Nc,
+    nc_modes: Option<TermModes>,
+    ncd: *mut NcDirect,
+    ncd_modes: Option<TermModes>,
+}
+
+// SAFETY: the pointers are only used by the hook while the lock is held,
+// and the contexts unregister themselves, taking the lock, before stopping.
+unsafe impl Send for Registered {}
+
+static INSTALL: Once = Once::new();
+
+/// Returns the locked registered contexts.
+fn registered() -> MutexGuard<'static, Registered> {
+    static INIT: Once = Once::new();
+    static REGISTERED: AtomicPtr<Mutex<Registered>> = AtomicPtr::new(null_mut());
+
+    INIT.call_once(|| {
+        let registered = Box::new(Mutex::new(Registered {
+            nc: null_mut(),
+            nc_modes: None,
+            ncd: null_mut(),
+            ncd_modes: None,
+        }));
+        REGISTERED.store(Box::into_raw(registered), Ordering::Release);
+    });
+    // SAFETY: REGISTERED is set just once, and never freed.
+    let registered = unsafe { &*REGISTERED.load(Ordering::Acquire) };
+    registered.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Installs the panic hook, just once, chaining the previous one.
+fn install() {
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            previous(info);
+        }));
+    });
+}
+
+/// Restores the terminal of the registered contexts, if any,
+/// without stopping them.
+fn restore_terminal() {
+    let registered = registered();
+    if !registered.nc.is_null() {
+        unsafe {
+            c_api::notcurses_leave_alternate_screen(registered.nc);
+        }
+    }
+    if !registered.ncd.is_null() {
+        unsafe {
+            c_api::ncdirect_set_fg_default(registered.ncd);
+            c_api::ncdirect_set_bg_default(registered.ncd);
+            c_api::ncdirect_set_styles(registered.ncd, 0);
+            c_api::ncdirect_cursor_enable(registered.ncd);
+            c_api::ncdirect_flush(registered.ncd);
+        }
+    }
+    for modes in registered.nc_modes.iter().chain(&registered.ncd_modes) {
+        modes.restore();
+    }
+}
+
+/// Registers `nc` to be restored when panicking, along with the `modes`
+/// saved before initializing it, installing the hook if needed.
+pub(crate) fn register_nc(nc: *mut Nc, modes: TermModes) {
+    install();
+    let mut registered = registered();
+    registered.nc = nc;
+    registered.nc_modes = Some(modes);
+}
+
+/// Unregisters `nc`, which must be done before stopping it.
+pub(crate) fn unregister_nc(nc: *mut Nc) {
+    let mut registered = registered();
+    if registered.nc == nc {
+        registered.nc = null_mut();
+        registered.nc_modes = None;
+    }
+}
+
+/// Registers `ncd` to be restored when panicking, along with the `modes`
+/// saved before initializing it, installing the hook if needed.
+pub(crate) fn register_ncd(ncd: *mut NcDirect, modes: TermModes) {
+    install();
+    let mut registered = registered();
+    registered.ncd = ncd;
+    registered.ncd_modes = Some(modes);
+}
+
+/// Unregisters `ncd`, which must be done before stopping it.
+pub(crate) fn unregister_ncd(ncd: *mut NcDirect) {
+    let mut registered = registered();
+    if registered.ncd == ncd {
+        registered.ncd = null_mut();
+        registered.ncd_modes = None;
+    }
+}
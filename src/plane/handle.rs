@@ -0,0 +1,491 @@
+//! `NcPlaneHandle`
+
+use core::{cell::Cell, marker::PhantomData, ptr::NonNull};
+
+use crate::{
+    c_api, error,
+    error::clear_errno,
+    plane::user_data::{free_family_data, free_plane_data},
+    NcAlign, NcAlpha, NcBlitter, NcBoxMask, NcCell, NcChannel, NcChannels, NcContext, NcError,
+    NcErrorKind, NcFile, NcGrid, NcGridDiff, NcPaletteIndex, NcPlane, NcPlaneOptions,
+    NcPlaneWriter, NcResizeStrategy, NcResult, NcRgb, NcRgba, NcStyle,
+};
+
+/// A handle to an [`NcPlane`] that can't outlive whatever it was created from.
+///
+/// A handle borrows from its owner: the [`NcContext`] for standard planes and
+/// for the root planes of new piles, or the parent handle for child planes.
+/// This means that:
+///
+/// - a handle can't be used after its [`NcContext`] has been stopped, nor
+///   after calling [`drop_planes`][crate::Nc#method.drop_planes].
+/// - [`destroy`][NcPlaneHandle#method.destroy] and
+///   [`family_destroy`][NcPlaneHandle#method.family_destroy] consume the
+///   handle, so it can't be used afterwards.
+/// - a parent can't be destroyed while there are handles to any of its
+///   children, since the whole family would be gone with it.
+///
+/// While any of its children are alive the parent can only be accessed
+/// through shared references, although its pile can still be rendered with
+/// [`render_pile`][NcPlaneHandle#method.render_pile].
+///
+/// Dropping a handle doesn't destroy the plane. It will be destroyed along
+/// with its family, or when the context stops.
+///
+/// It dereferences to [`NcPlane`], so all of its methods taking a shared
+/// reference are available. Of the methods taking an exclusive reference,
+/// it only provides the ones that can't destroy the plane nor return
+/// references to other planes. The rest can be reached through the unsafe
+/// [`as_plane_mut`][NcPlaneHandle#method.as_plane_mut].
+#[derive(Debug)]
+pub struct NcPlaneHandle<'a> {
+    plane: NonNull<NcPlane>,
+    // the flag of the context that is set while the standard plane is lent
+    stdplane_lent: Option<&'a Cell<bool>>,
+    _owner: PhantomData<&'a NcPlane>,
+}
+
+/// # `NcPlaneHandle` constructors
+impl NcContext {
+    /// Returns a handle to the standard plane, borrowing this context.
+    ///
+    /// The standard plane can't be destroyed, but the returned handle
+    /// still prevents the context from being stopped while it's alive.
+    ///
+    /// There can only be one handle to the standard plane at a time, so it
+    /// returns an error if the previous one is still alive.
+    ///
+    /// *C style function: [notcurses_stdplane()][c_api::notcurses_stdplane].*
+    pub fn stdplane_handle(&self) -> NcResult<NcPlaneHandle<'_>> {
+        if self.stdplane_lent.replace(true) {
+            return Err(NcError::with_kind(
+                NcErrorKind::Other,
+                "NcContext.stdplane_handle(): the standard plane is already lent",
+            ));
+        }
+        let ptr = unsafe { c_api::notcurses_stdplane(self.as_ptr()) };
+        // the standard plane always exists while the context is alive
+        let mut handle = unsafe { NcPlaneHandle::from_ptr(ptr).unwrap_unchecked() };
+        handle.stdplane_lent = Some(&self.stdplane_lent);
+        Ok(handle)
+    }
+
+    /// Creates a new pile, returning a handle to its root plane.
+    ///
+    /// *C style function: [ncpile_create()][c_api::ncpile_create].*
+    pub fn new_pile(&self, options: &NcPlaneOptions) -> NcResult<NcPlaneHandle<'_>> {
+        clear_errno();
+        let ptr = unsafe { c_api::ncpile_create(self.as_ptr(), options) };
+        NcPlaneHandle::from_ptr(ptr).ok_or_else(|| {
+            NcError::null_returned(
+                "ncpile_create",
+                &format!["NcContext.new_pile({:?})", options],
+            )
+        })
+    }
+
+    /// Creates a new pile with the specified position and size,
+    /// returning a handle to its root plane.
+    ///
+    /// *C style function: [ncpile_create()][c_api::ncpile_create].*
+    pub fn new_pile_sized(
+        &self,
+        y: i32,
+        x: i32,
+        rows: u32,
+        cols: u32,
+    ) -> NcResult<NcPlaneHandle<'_>> {
+        self.new_pile(&NcPlaneOptions::new(y, x, rows, cols))
+    }
+}
+
+/// # `NcPlaneHandle` constructors and destructors
+impl<'a> NcPlaneHandle<'a> {
+    /// Wraps a plane pointer, returning `None` if it's null.
+    pub(crate) fn from_ptr(ptr: *mut NcPlane) -> Option<Self> {
+        NonNull::new(ptr).map(|plane| Self { plane, stdplane_lent: None, _owner: PhantomData })
+    }
+
+    /// Creates a new child plane, bound to this one.
+    ///
+    /// The child borrows this handle, so this plane can't be destroyed
+    /// while the child handle is alive.
+    ///
+    /// *C style function: [ncplane_create()][c_api::ncplane_create].*
+    pub fn new_child(&self, options: &NcPlaneOptions) -> NcResult<NcPlaneHandle<'_>> {
+        clear_errno();
+        let ptr = unsafe { c_api::ncplane_create(self.as_ptr(), options) };
+        NcPlaneHandle::from_ptr(ptr).ok_or_else(|| {
+            NcError::null_returned(
+                "ncplane_create",
+                &format!["NcPlaneHandle.new_child({:?})", options],
+            )
+        })
+    }
+
+    /// Creates a new child plane with the specified position and size.
+    ///
+    /// *C style function: [ncplane_create()][c_api::ncplane_create].*
+    pub fn new_child_sized(
+        &self,
+        y: i32,
+        x: i32,
+        rows: u32,
+        cols: u32,
+    ) -> NcResult<NcPlaneHandle<'_>> {
+        self.new_child(&NcPlaneOptions::new(y, x, rows, cols))
+    }
+
+    /// Destroys this plane, consuming the handle.
+    ///
+    /// Any planes bound to it are rebound to its parent.
+    ///
+    /// It is an error to attempt to destroy the standard plane.
+    ///
+    /// Its [user data][NcPlane#method.set_user_data] is dropped.
+    ///
+    /// *C style function: [ncplane_destroy()][c_api::ncplane_destroy].*
+    pub fn destroy(self) -> NcResult<()> {
+        free_plane_data(self.as_ptr());
+        error![
+            unsafe { c_api::ncplane_destroy(self.as_ptr()) },
+            "NcPlaneHandle.destroy()"
+        ]
+    }
+
+    /// Destroys this plane and all of its bound descendants,
+    /// consuming the handle.
+    ///
+    /// It is an error to attempt to destroy the standard plane.
+    ///
+    /// The [user data][NcPlane#method.set_user_data] of the whole family
+    /// is dropped.
+    ///
+    /// *C style function: [ncplane_family_destroy()][c_api::ffi::ncplane_family_destroy].*
+    pub fn family_destroy(self) -> NcResult<()> {
+        free_family_data(&self);
+        error![
+            unsafe { c_api::ffi::ncplane_family_destroy(self.as_ptr()) },
+            "NcPlaneHandle.family_destroy()"
+        ]
+    }
+}
+
+/// # `NcPlaneHandle` methods
+impl<'a> NcPlaneHandle<'a> {
+    /// Returns the raw pointer to the wrapped [`NcPlane`].
+    pub fn as_ptr(&self) -> *mut NcPlane {
+        self.plane.as_ptr()
+    }
+
+    /// Returns an exclusive reference to the wrapped [`NcPlane`].
+    ///
+    /// # Safety
+    /// The plane must not be destroyed through the reference, and it must
+    /// not be used to get references to other planes that outlive it, since
+    /// they could alias with other handles.
+    pub unsafe fn as_plane_mut(&mut self) -> &mut NcPlane {
+        self.plane.as_mut()
+    }
+
+    /// Returns true if it's the standard plane.
+    pub(crate) fn is_stdplane(&self) -> bool {
+        let nc = unsafe { c_api::ncplane_notcurses_const(self.as_ptr()) };
+        self.as_ptr() as *const _ == unsafe { c_api::notcurses_stdplane_const(nc) }
+    }
+
+    /// Returns an exclusive reference to the plane, for the methods that
+    /// can't destroy it nor reach other planes.
+    pub(crate) fn plane_mut(&mut self) -> &mut NcPlane {
+        unsafe { self.plane.as_mut() }
+    }
+
+    /// Renders and rasterizes the pile of which this plane is a part.
+    ///
+    /// Unlike [`NcPlane::render_raster`], this only needs a shared reference,
+    /// so it can be called while there are handles to child planes.
+    ///
+    /// *C style functions: [ncpile_render()][c_api::ncpile_render] and
+    /// [ncpile_rasterize()][c_api::ncpile_rasterize].*
+    pub fn render_pile(&self) -> NcResult<()> {
+        unsafe { Self::render_pile_ptr(self.as_ptr(), "NcPlaneHandle.render_pile()") }
+    }
+
+    /// Like [`render_pile`][NcPlaneHandle#method.render_pile], but through a
+    /// raw pointer, reporting errors with the `caller`'s name.
+    ///
+    /// # Safety
+    /// `plane` must point to a valid plane.
+    pub(crate) unsafe fn render_pile_ptr(plane: *mut NcPlane, caller: &str) -> NcResult<()> {
+        clear_errno();
+        let res = c_api::ncpile_render(plane);
+        if res < c_api::NCRESULT_OK {
+            return Err(NcError::negative_return("ncpile_render", res, caller));
+        }
+        error![c_api::ncpile_rasterize(plane), caller]
+    }
+}
+
+/// Forwards methods of [`NcPlane`] taking an exclusive reference.
+macro_rules! forward_plane_mut {
+    ($( fn $name:ident $([$($gen:tt)*])? ($($arg:ident: $ty:ty),*) $(-> $ret:ty)?; )*) => {
+        $(
+            #[doc = concat!("See [`NcPlane::", stringify!($name), "`][NcPlane#method.",
+                stringify!($name), "].")]
+            pub fn $name $(<$($gen)*>)? (&mut self, $($arg: $ty),*) $(-> $ret)? {
+                self.plane_mut().$name($($arg),*)
+            }
+        )*
+    };
+}
+
+/// # `NcPlane` methods taking an exclusive reference
+impl<'a> NcPlaneHandle<'a> {
+    forward_plane_mut! {
+        // channels, styles and colors
+        fn set_fg_alpha(alpha: NcAlpha) -> NcResult<()>;
+        fn set_bg_alpha(alpha: NcAlpha) -> NcResult<()>;
+        fn set_channels(channels: NcChannels);
+        fn set_fchannel(channel: NcChannel) -> NcChannels;
+        fn set_bchannel(channel: NcChannel) -> NcChannels;
+        fn set_fg_rgb8(red: u8, green: u8, blue: u8);
+        fn set_bg_rgb8(red: u8, green: u8, blue: u8);
+        fn set_fg_rgb[RGB: Into<NcRgb>](rgb: RGB);
+        fn set_bg_rgb[RGB: Into<NcRgb>](rgb: RGB);
+        fn set_fg_default();
+        fn set_bg_default();
+        fn set_fg_not_default() -> NcChannels;
+        fn set_bg_not_default() -> NcChannels;
+        fn set_default() -> NcChannels;
+        fn set_not_default() -> NcChannels;
+        fn set_fg_palindex(palindex: NcPaletteIndex);
+        fn set_bg_palindex(palindex: NcPaletteIndex);
+        fn off_styles(stylemask: NcStyle);
+        fn on_styles(stylemask: NcStyle);
+        fn set_styles(stylemask: NcStyle);
+        fn stain(
+            y: Option<u32>, x: Option<u32>, len_y: Option<u32>, len_x: Option<u32>,
+            ul: NcChannels, ur: NcChannels, ll: NcChannels, lr: NcChannels
+        ) -> NcResult<u32>;
+        fn format(
+            y: Option<u32>, x: Option<u32>, len_y: Option<u32>, len_x: Option<u32>,
+            stylemask: NcStyle
+        ) -> NcResult<u32>;
+
+        // cells and contents
+        fn at_cursor(stylemask: &mut NcStyle, channels: &mut NcChannels) -> NcResult<String>;
+        fn at_cursor_cell(cell: &mut NcCell) -> NcResult<u32>;
+        fn at_yx(
+            y: u32, x: u32, stylemask: &mut NcStyle, channels: &mut NcChannels
+        ) -> NcResult<String>;
+        fn at_yx_cell(y: u32, x: u32, cell: &mut NcCell) -> NcResult<u32>;
+        fn base() -> NcResult<NcCell>;
+        fn set_base[S: Into<NcStyle> + Copy](
+            egc: &str, stylemask: S, channels: NcChannels
+        ) -> NcResult<u32>;
+        fn set_base_cell(cell: &NcCell) -> NcResult<()>;
+        fn contents(
+            beg_y: Option<u32>, beg_x: Option<u32>, len_y: Option<u32>, len_x: Option<u32>
+        ) -> String;
+        fn contents_grid(
+            beg_y: Option<u32>, beg_x: Option<u32>, len_y: Option<u32>, len_x: Option<u32>
+        ) -> NcResult<NcGrid>;
+        fn as_rgba(
+            blitter: NcBlitter, beg_y: Option<u32>, beg_x: Option<u32>, len_y: Option<u32>,
+            len_x: Option<u32>
+        ) -> NcResult<&mut [NcRgba]>;
+        fn erase();
+        fn erase_region(
+            beg_y: Option<u32>, beg_x: Option<u32>, len_y: i32, len_x: i32
+        ) -> NcResult<()>;
+
+        // output
+        fn putc_yx(y: u32, x: u32, cell: &NcCell) -> NcResult<u32>;
+        fn putc(cell: &NcCell) -> NcResult<u32>;
+        fn putchar(ch: char) -> NcResult<u32>;
+        fn putchar_stained(ch: char) -> NcResult<u32>;
+        fn putchar_yx(y: u32, x: u32, ch: char) -> NcResult<u32>;
+        fn putegc(egc: &str, sbytes: Option<&mut usize>) -> NcResult<u32>;
+        fn putegc_yx(
+            y: Option<u32>, x: Option<u32>, egc: &str, sbytes: Option<&mut usize>
+        ) -> NcResult<u32>;
+        fn putegc_stained(egc: &str, sbytes: Option<&mut usize>) -> NcResult<u32>;
+        fn puttext(y: u32, align: NcAlign, string: &str) -> NcResult<u32>;
+        fn putstr(string: &str) -> NcResult<u32>;
+        fn putstrln(string: &str) -> NcResult<u32>;
+        fn putln() -> NcResult<u32>;
+        fn putstr_stained(string: &str) -> NcResult<u32>;
+        fn putstr_aligned(y: Option<u32>, align: NcAlign, string: &str) -> NcResult<u32>;
+        fn putstr_yx(y: Option<u32>, x: Option<u32>, string: &str) -> NcResult<u32>;
+        fn putstr_aligned_stained(y: u32, align: NcAlign, string: &str) -> NcResult<u32>;
+        fn putstr_yx_stained(y: u32, x: u32, string: &str) -> NcResult<u32>;
+        fn putnstr(num_bytes: usize, string: &str) -> NcResult<u32>;
+        fn putnstr_aligned(
+            y: u32, align: NcAlign, num_bytes: usize, string: &str
+        ) -> NcResult<u32>;
+        fn putnstr_yx(
+            y: Option<u32>, x: Option<u32>, num_bytes: usize, string: &str
+        ) -> NcResult<u32>;
+        fn putmarkup(y: Option<u32>, align: NcAlign, markup: &str) -> NcResult<u32>;
+        fn polyfill_yx(y: u32, x: u32, cell: &NcCell) -> NcResult<usize>;
+        fn writer() -> NcPlaneWriter<'_>;
+        fn styled(styles: NcStyle) -> NcPlaneWriter<'_>;
+
+        // position, stacking and rendering
+        fn move_bottom();
+        fn move_top();
+        fn move_family_bottom();
+        fn move_family_top();
+        fn move_yx(y: i32, x: i32) -> NcResult<()>;
+        fn move_rel(rows: i32, cols: i32) -> NcResult<()>;
+        fn set_name(name: Option<&str>) -> NcResult<()>;
+        fn rasterize() -> NcResult<()>;
+        fn render() -> NcResult<()>;
+        fn render_raster() -> NcResult<()>;
+        fn render_to_buffer(buffer: &mut Vec<u8>) -> NcResult<()>;
+        fn render_to_grid() -> NcResult<NcGrid>;
+        fn render_to_file(fp: &mut NcFile) -> NcResult<()>;
+
+        // cursor
+        fn cursor_home();
+        fn cursor_move_yx(y: u32, x: u32) -> NcResult<()>;
+        fn cursor_move_y(y: u32) -> NcResult<()>;
+        fn cursor_move_x(x: u32) -> NcResult<()>;
+        fn cursor_move_rows(rows: i32) -> NcResult<()>;
+        fn cursor_move_cols(cols: i32) -> NcResult<()>;
+        fn cursor_move_rel(rows: i32, cols: i32) -> NcResult<()>;
+        fn halign(align: NcAlign, numcols: u32) -> NcResult<u32>;
+        fn valign(align: NcAlign, numrows: u32) -> NcResult<u32>;
+
+        // size and scrolling
+        fn resize(
+            keep_y: u32, keep_x: u32, keep_len_y: u32, keep_len_x: u32, off_y: i32, off_x: i32,
+            len_y: u32, len_x: u32
+        ) -> NcResult<()>;
+        fn resize_marginalized() -> NcResult<()>;
+        fn resize_maximize() -> NcResult<()>;
+        fn resize_placewithin() -> NcResult<()>;
+        fn resize_realign() -> NcResult<()>;
+        fn resize_simple(len_y: u32, len_x: u32) -> NcResult<()>;
+        fn set_resize_strategy(strategy: NcResizeStrategy);
+        fn rotate_cw() -> NcResult<()>;
+        fn rotate_ccw() -> NcResult<()>;
+        fn set_scrolling(scroll: bool) -> bool;
+        fn scrollup(r: u32) -> NcResult<u32>;
+        fn scrollup_child(child: &NcPlane) -> NcResult<u32>;
+        fn set_autogrow(autogrow: bool) -> bool;
+
+        // boxes, gradients and other drawing
+        fn box_sized(
+            ul: &NcCell, ur: &NcCell, ll: &NcCell, lr: &NcCell, hline: &NcCell, vline: &NcCell,
+            len_y: u32, len_x: u32, boxmask: NcBoxMask
+        ) -> NcResult<()>;
+        fn ascii_box(
+            stylemask: NcStyle, channels: NcChannels, stop_y: u32, stop_x: u32,
+            boxmask: NcBoxMask
+        ) -> NcResult<()>;
+        fn double_box(
+            stylemask: NcStyle, channels: NcChannels, stop_y: u32, stop_x: u32,
+            boxmask: NcBoxMask
+        ) -> NcResult<()>;
+        fn double_box_sized(
+            stylemask: NcStyle, channels: NcChannels, len_y: u32, len_x: u32,
+            boxmask: NcBoxMask
+        ) -> NcResult<()>;
+        fn perimeter(
+            ul: &NcCell, ur: &NcCell, ll: &NcCell, lr: &NcCell, hline: &NcCell, vline: &NcCell,
+            boxmask: NcBoxMask
+        ) -> NcResult<()>;
+        fn perimeter_double(
+            stylemask: NcStyle, channels: NcChannels, boxmask: NcBoxMask
+        ) -> NcResult<()>;
+        fn perimeter_rounded(
+            stylemask: NcStyle, channels: NcChannels, boxmask: NcBoxMask
+        ) -> NcResult<()>;
+        fn gradient(
+            y: Option<u32>, x: Option<u32>, stop_y: Option<u32>, stop_x: Option<u32>,
+            egc: &str, stylemask: NcStyle, ul: NcChannels, ur: NcChannels, ll: NcChannels,
+            lr: NcChannels
+        ) -> NcResult<u32>;
+        fn gradient2x1(
+            y: Option<u32>, x: Option<u32>, len_y: Option<u32>, len_x: Option<u32>,
+            ul: NcChannel, ur: NcChannel, ll: NcChannel, lr: NcChannel
+        ) -> NcResult<u32>;
+        fn greyscale();
+        fn qrcode(data: &mut [u8]) -> NcResult<(u32, u32, u32)>;
+
+        // user data
+        fn set_user_data[T: 'static](data: T);
+        fn user_data_mut[T: 'static]() -> Option<&mut T>;
+        fn take_user_data[T: 'static]() -> Option<T>;
+    }
+
+    /// Splices this plane out of the z-buffer, and reinserts it above
+    /// `above`.
+    ///
+    /// See [`NcPlane::move_above`][NcPlane#method.move_above].
+    pub fn move_above(&mut self, above: &mut NcPlaneHandle<'_>) -> NcResult<()> {
+        self.plane_mut().move_above(above.plane_mut())
+    }
+
+    /// Splices this plane out of the z-buffer, and reinserts it below
+    /// `below`.
+    ///
+    /// See [`NcPlane::move_below`][NcPlane#method.move_below].
+    pub fn move_below(&mut self, below: &mut NcPlaneHandle<'_>) -> NcResult<()> {
+        self.plane_mut().move_below(below.plane_mut())
+    }
+
+    /// Splices this plane and its bound planes out of the z-buffer,
+    /// and reinserts them above `above`.
+    ///
+    /// See [`NcPlane::move_family_above`][NcPlane#method.move_family_above].
+    pub fn move_family_above(&mut self, above: &mut NcPlaneHandle<'_>) -> NcResult<()> {
+        self.plane_mut().move_family_above(above.plane_mut())
+    }
+
+    /// Splices this plane and its bound planes out of the z-buffer,
+    /// and reinserts them below `below`.
+    ///
+    /// See [`NcPlane::move_family_below`][NcPlane#method.move_family_below].
+    pub fn move_family_below(&mut self, below: &mut NcPlaneHandle<'_>) -> NcResult<()> {
+        self.plane_mut().move_family_below(below.plane_mut())
+    }
+
+    /// Merges the whole `source` plane down onto this plane.
+    ///
+    /// See [`NcPlane::mergedown_simple`][NcPlane#method.mergedown_simple].
+    pub fn mergedown_simple(&mut self, source: &mut NcPlaneHandle<'_>) -> NcResult<()> {
+        self.plane_mut().mergedown_simple(source.plane_mut())
+    }
+
+    /// Returns the cells that differ between this plane and a `new` one.
+    ///
+    /// See [`NcPlane::diff`][NcPlane#method.diff].
+    pub fn diff(&mut self, new: &mut NcPlaneHandle<'_>) -> NcResult<NcGridDiff> {
+        self.plane_mut().diff(new.plane_mut())
+    }
+}
+
+mod std_impls {
+    use super::NcPlaneHandle;
+    use crate::NcPlane;
+    use core::ops::Deref;
+
+    impl<'a> Deref for NcPlaneHandle<'a> {
+        type Target = NcPlane;
+
+        fn deref(&self) -> &NcPlane {
+            unsafe { self.plane.as_ref() }
+        }
+    }
+
+    /// Gives back the standard plane to its context.
+    impl<'a> Drop for NcPlaneHandle<'a> {
+        fn drop(&mut self) {
+            if let Some(lent) = self.stdplane_lent {
+                lent.set(false);
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! `NcHit`
+
+use crate::{c_api, NcAlpha, NcChannels, NcPlane, NcStyle};
+
+/// The result of a hit test: the plane found at some absolute coordinates,
+/// and the coordinates relative to its origin.
+///
+/// It's returned by [`NcPlane::hit_test`][NcPlane#method.hit_test].
+#[derive(Debug)]
+pub struct NcHit<'a> {
+    /// The topmost plane that is not transparent at the tested coordinates.
+    pub plane: &'a mut NcPlane,
+    /// The row, relative to the plane's origin.
+    pub y: u32,
+    /// The column, relative to the plane's origin.
+    pub x: u32,
+}
+
+/// # `NcPlane` hit-testing
+impl NcPlane {
+    /// Returns the topmost plane of this plane's pile covering the absolute
+    /// coordinates `y`, `x`, along with the coordinates relative to it.
+    ///
+    /// The pile is walked along the z-axis from the top. Planes where the
+    /// cell at those coordinates is transparent, that is, it has no glyph and
+    /// a transparent background (taking the base cell into account), are
+    /// skipped, since they don't hide the planes below.
+    ///
+    /// The coordinates of mouse events are relative to the terminal's origin,
+    /// which corresponds to the origin of the rendered pile.
+    ///
+    /// Returns `None` if no plane is hit.
+    ///
+    /// *(No equivalent C style function)*
+    pub fn hit_test(&mut self, y: i32, x: i32) -> Option<NcHit<'_>> {
+        let mut ptr = unsafe { c_api::ncpile_top(self) };
+        while !ptr.is_null() {
+            let plane = unsafe { &mut *ptr };
+            let (mut abs_y, mut abs_x) = (0, 0);
+            unsafe { c_api::ncplane_abs_yx(plane, &mut abs_y, &mut abs_x) };
+            let (rows, cols) = plane.dim_yx();
+            let (ly, lx) = (y - abs_y, x - abs_x);
+            if ly >= 0
+                && lx >= 0
+                && (ly as u32) < rows
+                && (lx as u32) < cols
+                && !plane.is_transparent_at(ly as u32, lx as u32)
+            {
+                return Some(NcHit { plane, y: ly as u32, x: lx as u32 });
+            }
+            ptr = unsafe { c_api::ncplane_below(ptr) };
+        }
+        None
+    }
+
+    /// Returns true if the cell at `y`, `x` has no glyph and a transparent
+    /// background, or if the coordinates are out of bounds.
+    ///
+    /// A cell without a glyph is rendered as the base cell, so then it's
+    /// the base cell that is checked.
+    fn is_transparent_at(&mut self, y: u32, x: u32) -> bool {
+        let mut style = NcStyle::None;
+        let mut channels = NcChannels::new();
+        match self.at_yx(y, x, &mut style, &mut channels) {
+            Ok(egc) if egc.is_empty() => match self.base() {
+                Ok(mut base) => {
+                    let transparent =
+                        base.egc(self).is_empty() && base.bg_alpha() == NcAlpha::Transparent;
+                    base.release(self);
+                    transparent
+                }
+                Err(_) => true,
+            },
+            Ok(_) => false,
+            Err(_) => true,
+        }
+    }
+}
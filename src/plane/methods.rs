@@ -1,10 +1,16 @@
 //! `NcPlane*` methods and associated functions.
-use core::{ptr::null_mut, slice::from_raw_parts_mut};
+use core::{
+    ptr::{null, null_mut},
+    slice::{from_raw_parts, from_raw_parts_mut},
+};
+use std::ffi::CString;
 
 use crate::{
-    c_api, cstring, error, error_ref, error_ref_mut, rstring_free, Nc, NcAlign, NcAlpha, NcBlitter,
-    NcBoxMask, NcCell, NcChannel, NcChannels, NcError, NcFadeCb, NcFile, NcPaletteIndex,
-    NcPixelGeometry, NcPlane, NcPlaneOptions, NcResizeCb, NcResult, NcRgb, NcRgba, NcStyle, NcTime,
+    c_api, cstring, error, error::clear_errno, error_ref, error_ref_mut,
+    plane::user_data::free_plane_data, rstring_free, Nc, NcAlign, NcAlpha, NcBlitter, NcBoxMask,
+    NcCell, NcChannel, NcChannels, NcError, NcErrorKind, NcFadeCb, NcFile, NcGrid, NcGridCell,
+    NcPaletteIndex, NcPixelGeometry, NcPlane, NcPlaneOptions, NcResizeCb, NcResult, NcRgb, NcRgba,
+    NcStyle, NcTime,
 };
 
 /// # NcPlane constructors & destructors
@@ -91,8 +97,11 @@ impl NcPlane {
     ///
     /// It is an error to attempt to destroy the standard plane.
     ///
+    /// Its [user data][NcPlane#method.set_user_data] is dropped.
+    ///
     /// *C style function: [ncplane_destroy()][c_api::ncplane_destroy].*
     pub fn destroy(&mut self) -> NcResult<()> {
+        free_plane_data(self);
         error![unsafe { c_api::ncplane_destroy(self) }, "NcPlane.destroy()"]
     }
 }
@@ -225,6 +234,7 @@ impl NcPlane {
             )
         };
         error![
+            @fn "ncplane_stain",
             res,
             &format!(
                 "NcPlane.stain({:?}, {:?}, {:?}, {:?}, {:0X}, {:0X}, {:0X}, {:0X})",
@@ -449,6 +459,7 @@ impl NcPlane {
             )
         };
         error![
+            @fn "ncplane_format",
             res,
             &format!(
                 "NcPlane.format({:?}, {:?}, {:?}, {:?}, {:0X})",
@@ -549,6 +560,7 @@ impl NcPlane {
     pub fn at_cursor_cell(&mut self, cell: &mut NcCell) -> NcResult<u32> {
         let bytes = unsafe { c_api::ncplane_at_cursor_cell(self, cell) };
         error![
+            @fn "ncplane_at_cursor_cell",
             bytes,
             &format!("NcPlane.at_cursor_cell({:?})", cell),
             bytes as u32
@@ -603,6 +615,7 @@ impl NcPlane {
     pub fn at_yx_cell(&mut self, y: u32, x: u32, cell: &mut NcCell) -> NcResult<u32> {
         let bytes = unsafe { c_api::ncplane_at_yx_cell(self, y as i32, x as i32, cell) };
         error![
+            @fn "ncplane_at_yx_cell",
             bytes,
             &format!("NcPlane.at_yx_cell({}, {}, {:?})", y, x, cell),
             bytes as u32
@@ -616,8 +629,9 @@ impl NcPlane {
     /// *C style function: [ncplane_base()][c_api::ncplane_base].*
     pub fn base(&mut self) -> NcResult<NcCell> {
         let mut cell = NcCell::new();
+        clear_errno();
         let res = unsafe { c_api::ncplane_base(self, &mut cell) };
-        error![res, "NcPlane.base()", cell]
+        error![@fn "ncplane_base", res, "NcPlane.base()", cell]
     }
 
     /// Sets this `NcPlane`'s base [`NcCell`] from its components.
@@ -647,6 +661,7 @@ impl NcPlane {
             c_api::ncplane_set_base(self, cstring![egc], stylemask.into().into(), channels.0)
         };
         error![
+            @fn "ncplane_set_base",
             res,
             &format!(
                 "NcPlane.set_base({:?}, {:0X}, {:0X})",
@@ -702,6 +717,71 @@ impl NcPlane {
         )]
     }
 
+    /// Exports the selected region of the `NcPlane` to an [`NcGrid`] of
+    /// owned cells, with their `EGC`s, styles, channels and widths.
+    ///
+    /// The region is selected like in [`contents`][NcPlane#method.contents].
+    ///
+    /// The grid doesn't depend on the plane, so it can be inspected and
+    /// compared after the plane has changed or been destroyed. Undrawn cells
+    /// have an empty `EGC`, and the base cell is not taken into account.
+    ///
+    /// *(No equivalent C style function)*
+    pub fn contents_grid(
+        &mut self,
+        beg_y: Option<u32>,
+        beg_x: Option<u32>,
+        len_y: Option<u32>,
+        len_x: Option<u32>,
+    ) -> NcResult<NcGrid> {
+        let (dim_y, dim_x) = self.dim_yx();
+        let (cursor_y, cursor_x) = self.cursor_yx();
+        let (beg_y, beg_x) = (beg_y.unwrap_or(cursor_y), beg_x.unwrap_or(cursor_x));
+        if beg_y >= dim_y || beg_x >= dim_x {
+            return Err(NcError::with_kind(
+                NcErrorKind::InvalidArgument,
+                &format![
+                    "NcPlane.contents_grid(): {}×{} is out of the plane",
+                    beg_y, beg_x
+                ],
+            ));
+        }
+        let rows = len_y.filter(|&l| l > 0).unwrap_or(dim_y - beg_y);
+        let cols = len_x.filter(|&l| l > 0).unwrap_or(dim_x - beg_x);
+        if rows > dim_y - beg_y || cols > dim_x - beg_x {
+            return Err(NcError::with_kind(
+                NcErrorKind::InvalidArgument,
+                &format![
+                    "NcPlane.contents_grid(): {}×{} exceeds the plane",
+                    rows, cols
+                ],
+            ));
+        }
+
+        let mut grid = NcGrid::new(rows, cols);
+        let mut cell = NcCell::new();
+        for y in 0..rows {
+            for x in 0..cols {
+                self.at_yx_cell(beg_y + y, beg_x + x, &mut cell)?;
+                let (egc, width) = if cell.wide_right_p() {
+                    (String::new(), 0)
+                } else {
+                    (cell.extended_gcluster(self).to_owned(), cell.cols())
+                };
+                if let Some(grid_cell) = grid.get_mut(y, x) {
+                    *grid_cell = NcGridCell {
+                        egc,
+                        style: cell.styles(),
+                        channels: cell.channels.into(),
+                        width,
+                    };
+                }
+                cell.release(self);
+            }
+        }
+        Ok(grid)
+    }
+
     /// Erases every [`NcCell`] in this `NcPlane`, resetting all attributes to
     /// normal, all colors to the default color, and all cells to undrawn.
     ///
@@ -788,6 +868,7 @@ impl NcPlane {
     pub fn putc_yx(&mut self, y: u32, x: u32, cell: &NcCell) -> NcResult<u32> {
         let res = unsafe { c_api::ncplane_putc_yx(self, y as i32, x as i32, cell) };
         error![
+            @fn "ncplane_putc_yx",
             res,
             &format!("NcPlane.putc_yx({}, {}, {:?})", y, x, cell),
             res as u32
@@ -807,8 +888,9 @@ impl NcPlane {
     ///
     /// *C style function: [ncplane_putc()][c_api::ncplane_putc].*
     pub fn putc(&mut self, cell: &NcCell) -> NcResult<u32> {
+        clear_errno();
         let res = c_api::ncplane_putc(self, cell);
-        error![res, &format!("NcPlane.putc({:?})", cell), res as u32]
+        error![@fn "ncplane_putc", res, &format!("NcPlane.putc({:?})", cell), res as u32]
     }
 
     /// Calls [`putchar_yx`][NcPlane#method.putchar_yx] at the current cursor
@@ -823,8 +905,9 @@ impl NcPlane {
     ///
     /// *C style function: [ncplane_putchar()][c_api::ncplane_putchar].*
     pub fn putchar(&mut self, ch: char) -> NcResult<u32> {
+        clear_errno();
         let res = c_api::ncplane_putchar(self, ch);
-        error![res, &format!("NcPlane.putchar({:?})", ch), res as u32]
+        error![@fn "ncplane_putchar", res, &format!("NcPlane.putchar({:?})", ch), res as u32]
     }
 
     /// Replaces the [`NcCell`] at the current location with the provided `char`,
@@ -842,6 +925,7 @@ impl NcPlane {
     pub fn putchar_stained(&mut self, ch: char) -> NcResult<u32> {
         let res = c_api::ncplane_putchar_stained(self, ch);
         error![
+            @fn "ncplane_putchar_stained",
             res,
             &format!("NcPlane.putchar_stained({:?})", ch),
             res as u32
@@ -862,6 +946,7 @@ impl NcPlane {
     pub fn putchar_yx(&mut self, y: u32, x: u32, ch: char) -> NcResult<u32> {
         let res = c_api::ncplane_putchar_yx(self, y, x, ch);
         error![
+            @fn "ncplane_putchar_yx",
             res,
             &format!("NcPlane.putchar_yx({}, {}, {:?})", y, x, ch),
             res as u32
@@ -884,8 +969,9 @@ impl NcPlane {
     ///
     /// *C style function: [ncplane_putegc()][c_api::ncplane_putegc].*
     pub fn putegc(&mut self, egc: &str, sbytes: Option<&mut usize>) -> NcResult<u32> {
+        clear_errno();
         let res = c_api::ncplane_putegc(self, egc, sbytes);
-        error![res, &format!("NcPlane.putegc({:?}, …)", egc), res as u32]
+        error![@fn "ncplane_putegc", res, &format!("NcPlane.putegc({:?}, …)", egc), res as u32]
     }
 
     /// Replaces the [`NcCell`] at the specified coordinates with the provided
@@ -912,6 +998,7 @@ impl NcPlane {
     ) -> NcResult<u32> {
         let res = c_api::ncplane_putegc_yx(self, y, x, egc, sbytes);
         error![
+            @fn "ncplane_putegc_yx",
             res,
             &format!("NcPlane.putegc_yx({:?}, {:?}, {:?}, …)", y, x, egc),
             res as u32
@@ -936,6 +1023,7 @@ impl NcPlane {
     pub fn putegc_stained(&mut self, egc: &str, sbytes: Option<&mut usize>) -> NcResult<u32> {
         let res = c_api::ncplane_putegc_stained(self, egc, sbytes);
         error![
+            @fn "ncplane_putegc_stained",
             res,
             &format!("NcPlane.putegc_stained({:?}, …)", egc),
             res as u32
@@ -967,10 +1055,11 @@ impl NcPlane {
     ///
     /// *C style function: [ncplane_puttext()][c_api::ncplane_puttext].*
     pub fn puttext(&mut self, y: u32, align: NcAlign, string: &str) -> NcResult<u32> {
+        clear_errno();
         let res = unsafe {
             c_api::ncplane_puttext(self, y as i32, align.into(), cstring![string], null_mut())
         };
-        error![res, &format!("NcPlane.puttext({:?})", string), res as u32]
+        error![@fn "ncplane_puttext", res, &format!("NcPlane.puttext({:?})", string), res as u32]
     }
 
     /// Writes a string to the current location, using the current style.
@@ -987,8 +1076,9 @@ impl NcPlane {
     /// *C style function: [ncplane_putstr()][c_api::ncplane_putstr].*
     #[inline]
     pub fn putstr(&mut self, string: &str) -> NcResult<u32> {
+        clear_errno();
         let res = c_api::ncplane_putstr(self, string);
-        error![res, &format!("NcPlane.putstr({:?})", string), res as u32]
+        error![@fn "ncplane_putstr", res, &format!("NcPlane.putstr({:?})", string), res as u32]
     }
 
     /// Same as [`putstr`][NcPlane#method.putstr], but it also puts a newline
@@ -1028,6 +1118,7 @@ impl NcPlane {
     pub fn putstr_stained(&mut self, string: &str) -> NcResult<u32> {
         let res = c_api::ncplane_putstr_stained(self, string);
         error![
+            @fn "ncplane_putstr_stained",
             res,
             &format!("NcPlane.putstr_stained({:?})", string),
             res as u32
@@ -1055,6 +1146,7 @@ impl NcPlane {
     ) -> NcResult<u32> {
         let res = c_api::ncplane_putstr_aligned(self, y, align, string);
         error![
+            @fn "ncplane_putstr_aligned",
             res,
             &format!("NcPlane.putstr_aligned({:?}, {}, {:?})", y, align, string),
             res as u32
@@ -1076,6 +1168,7 @@ impl NcPlane {
     pub fn putstr_yx(&mut self, y: Option<u32>, x: Option<u32>, string: &str) -> NcResult<u32> {
         let res = c_api::ncplane_putstr_yx(self, y, x, string);
         error![
+            @fn "ncplane_putstr_yx",
             res,
             &format!("NcPlane.putstr_yx({:?}, {:?}, {:?})", y, x, string),
             res as u32
@@ -1106,6 +1199,7 @@ impl NcPlane {
         self.cursor_move_yx(y, xpos)?;
         let res = c_api::ncplane_putstr_stained(self, string);
         error![
+            @fn "ncplane_putstr_stained",
             res,
             &format!(
                 "NcPlane.putstr_aligned_stained({}, {}, {:?})",
@@ -1132,6 +1226,7 @@ impl NcPlane {
         self.cursor_move_yx(y, x)?;
         let res = c_api::ncplane_putstr_stained(self, string);
         error![
+            @fn "ncplane_putstr_stained",
             res,
             &format!("NcPlane.putstr_yx_stained({}, {}, {:?})", y, x, string),
             res as u32
@@ -1155,6 +1250,7 @@ impl NcPlane {
     pub fn putnstr(&mut self, num_bytes: usize, string: &str) -> NcResult<u32> {
         let res = c_api::ncplane_putnstr(self, num_bytes, string);
         error![
+            @fn "ncplane_putnstr",
             res,
             &format!("NcPlane.puntstr({}, {:?})", num_bytes, string),
             res as u32
@@ -1191,6 +1287,7 @@ impl NcPlane {
             )
         };
         error![
+            @fn "ncplane_putnstr_aligned",
             res,
             &format!(
                 "NcPlane.putnstr_aligned({}, {}, {}, {:?})",
@@ -1222,6 +1319,7 @@ impl NcPlane {
     ) -> NcResult<u32> {
         let res = c_api::ncplane_putnstr_yx(self, y, x, num_bytes, string);
         error![
+            @fn "ncplane_putnstr_yx",
             res,
             &format!(
                 "NcPlane.putnstr_yx({:?}, {:?}, {}, {:?})",
@@ -1242,6 +1340,7 @@ impl NcPlane {
     pub fn polyfill_yx(&mut self, y: u32, x: u32, cell: &NcCell) -> NcResult<usize> {
         let res = unsafe { c_api::ncplane_polyfill_yx(self, y as i32, x as i32, cell) };
         error![
+            @fn "ncplane_polyfill_yx",
             res,
             &format!("NcPlane.polyfill_yx({}, {}, {:?})", y, x, cell),
             res as usize
@@ -1287,9 +1386,9 @@ impl NcPlane {
     /// and will be bound to the same parent. Bound planes are not duplicated;
     /// the new plane is bound to the current parent, but has no bound planes.
     ///
+    /// The [user data][NcPlane#method.set_user_data] is not duplicated.
+    ///
     /// *C style function: [ncplane_dup()][c_api::ncplane_dup].*
-    //
-    // TODO: deal with the opaque field that is stored in NcPlaneOptions.userptr
     pub fn dup(&mut self) -> &mut NcPlane {
         unsafe { &mut *c_api::ncplane_dup(self, null_mut()) }
     }
@@ -1358,6 +1457,29 @@ impl NcPlane {
         ]
     }
 
+    /// Returns the name of this `NcPlane`, or `None` if it doesn't have one.
+    ///
+    /// *C style function: [ncplane_name()][c_api::ncplane_name].*
+    pub fn name(&self) -> Option<String> {
+        let name = unsafe { c_api::ncplane_name(self) };
+        if name.is_null() {
+            None
+        } else {
+            Some(rstring_free![name])
+        }
+    }
+
+    /// Sets the name of this `NcPlane`, or removes it if `None`.
+    ///
+    /// *C style function: [ncplane_set_name()][c_api::ncplane_set_name].*
+    pub fn set_name(&mut self, name: Option<&str>) -> NcResult<()> {
+        let cname = name.map(CString::new).transpose()?;
+        error![
+            unsafe { c_api::ncplane_set_name(self, cname.as_ref().map_or(null(), |n| n.as_ptr())) },
+            &format!("NcPlane.set_name({:?})", name)
+        ]
+    }
+
     /// Returns the `NcPlane` above this one, or None if already at the top.
     ///
     /// *C style function: [ncplane_above()][c_api::ncplane_above].*
@@ -1632,28 +1754,51 @@ impl NcPlane {
         Ok(())
     }
 
-    /// Performs the rendering and rasterization portion of
-    /// [`render`][NcPlane#method.render] and [`rasterize`][NcPlane#method.rasterize]
-    /// but doe not write the resulting buffer out to the terminal.
+    /// Performs the rasterization portion of
+    /// [`rasterize`][NcPlane#method.rasterize] for the last rendered frame,
+    /// but does not write the resulting buffer out to the terminal.
+    ///
+    /// The escape sequences that would have been written are copied into
+    /// `buffer`, replacing its previous contents.
     ///
     /// Using this function, the user can control the writeout process.
-    /// The returned buffer must be freed by the caller.
     ///
     /// *C style function: [ncpile_render_to_buffer()][c_api::ncpile_render_to_buffer].*
-    // CHECK this works
     pub fn render_to_buffer(&mut self, buffer: &mut Vec<u8>) -> NcResult<()> {
-        let len = buffer.len() as u32;
-
-        // https://github.com/dankamongmen/notcurses/issues/1339
-        #[cfg(any(target_arch = "x86_64", target_arch = "i686", target_arch = "x86"))]
-        let mut buf = buffer.as_mut_ptr() as *mut i8;
-        #[cfg(not(any(target_arch = "x86_64", target_arch = "i686", target_arch = "x86")))]
-        let mut buf = buffer.as_mut_ptr() as *mut u8;
+        let mut buf = null_mut();
+        let mut len = 0;
+        clear_errno();
+        let res = unsafe { c_api::ncpile_render_to_buffer(self, &mut buf, &mut len) };
+        buffer.clear();
+        if res < 0 {
+            return Err(NcError::negative_return(
+                "ncpile_render_to_buffer",
+                res,
+                "NcPlane.render_to_buffer()",
+            ));
+        }
+        if !buf.is_null() {
+            buffer.extend_from_slice(unsafe { from_raw_parts(buf as *const u8, len) });
+            unsafe { c_api::libc::free(buf as *mut core::ffi::c_void) };
+        }
+        Ok(())
+    }
 
-        error![
-            unsafe { c_api::ncpile_render_to_buffer(self, &mut buf, &mut len.try_into().unwrap()) },
-            &format!["NcPlane.render_to_buffer(buffer, {})", len]
-        ]
+    /// Renders the pile of which this `NcPlane` is a part, and returns the
+    /// resulting frame as an [`NcGrid`] of the terminal's size.
+    ///
+    /// The frame is obtained by interpreting the escape sequences returned by
+    /// [`render_to_buffer`][NcPlane#method.render_to_buffer]. Since those only
+    /// update the cells that changed since the last rasterized frame, the grid
+    /// is complete only if nothing has been rasterized before.
+    ///
+    /// *(No equivalent C style function)*
+    pub fn render_to_grid(&mut self) -> NcResult<NcGrid> {
+        let (rows, cols) = unsafe { self.notcurses_const()? }.term_dim_yx();
+        self.render()?;
+        let mut buffer = vec![];
+        self.render_to_buffer(&mut buffer)?;
+        Ok(NcGrid::from_escapes(&buffer, rows, cols))
     }
 
     /// Writes the last rendered frame, in its entirety, to `fp`.
@@ -1810,6 +1955,7 @@ impl NcPlane {
     pub fn halign(&mut self, align: NcAlign, numcols: u32) -> NcResult<u32> {
         let res = c_api::ncplane_halign(self, align, numcols);
         error![
+            @fn "ncplane_halign",
             res,
             &format!("NcPlane.halign({:?}, {})", align, numcols),
             res as u32
@@ -1827,6 +1973,7 @@ impl NcPlane {
     pub fn valign(&mut self, align: NcAlign, numrows: u32) -> NcResult<u32> {
         let res = c_api::ncplane_valign(self, align, numrows);
         error![
+            @fn "ncplane_valign",
             res,
             &format!("NcPlane.valign({:?}, {})", align, numrows),
             res as u32
@@ -2218,8 +2365,9 @@ impl NcPlane {
     ///
     /// *C style function: [ncplane_scrollup()][c_api::ncplane_scrollup].*
     pub fn scrollup(&mut self, r: u32) -> NcResult<u32> {
+        clear_errno();
         let res = unsafe { c_api::ncplane_scrollup(self, r as i32) };
-        error![res, "", res as u32]
+        error![@fn "ncplane_scrollup", res, "", res as u32]
     }
 
     /// Scrolls down the current plane until `child` is no longer hidden beneath it.
@@ -2231,8 +2379,9 @@ impl NcPlane {
     ///
     /// *C style function: [ncplane_scrollup_child()][c_api::ncplane_scrollup_child].*
     pub fn scrollup_child(&mut self, child: &NcPlane) -> NcResult<u32> {
+        clear_errno();
         let res = unsafe { c_api::ncplane_scrollup_child(self, child) };
-        error![res, "", res as u32]
+        error![@fn "ncplane_scrollup_child", res, "", res as u32]
     }
 
     /// Returns `true` if this `NcPlane` has autogrow enabled, or `false` otherwise.
@@ -2564,6 +2713,7 @@ impl NcPlane {
         ll: NcChannels,
         lr: NcChannels,
     ) -> NcResult<u32> {
+        clear_errno();
         let res = c_api::ncplane_gradient(
             self,
             y,
@@ -2577,7 +2727,7 @@ impl NcPlane {
             ll.into(),
             lr.into(),
         );
-        error![res, "", res as u32]
+        error![@fn "ncplane_gradient", res, "", res as u32]
     }
 
     /// Does a high-resolution gradient using upper blocks and synced backgrounds.
@@ -2605,6 +2755,7 @@ impl NcPlane {
         ll: NcChannel,
         lr: NcChannel,
     ) -> NcResult<u32> {
+        clear_errno();
         let res = unsafe {
             c_api::ncplane_gradient2x1(
                 self,
@@ -2618,7 +2769,7 @@ impl NcPlane {
                 lr.into(),
             )
         };
-        error![res, "", res as u32]
+        error![@fn "ncplane_gradient2x1", res, "", res as u32]
     }
 
     /// Converts this `NcPlane`'s content to greyscale.
@@ -2651,6 +2802,7 @@ impl NcPlane {
         let data_ptr = data.as_ptr() as *const std::ffi::c_void;
         let res = unsafe { c_api::ncplane_qrcode(self, &mut max_y, &mut max_x, data_ptr, len) };
         error![
+            @fn "ncplane_qrcode",
             res,
             &format!("NcPlane.qrcode(data:{:?})", data),
             (res as u32, max_y, max_x)
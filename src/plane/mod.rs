@@ -1,9 +1,9 @@
 //! `NcPlane`
 
-// functions already exported by bindgen : 100
+// functions already exported by bindgen : 102
 // -------------------------------------------
 // (#) test: 9
-// (W) wrap: 93
+// (W) wrap: 97
 // -------------------------------------------
 //W  ncpile_bottom
 //W# ncpile_create
@@ -52,6 +52,7 @@
 //W  ncplane_move_family_above
 //W  ncplane_move_family_below
 //W  ncplane_move_yx
+//W  ncplane_name
 //W# ncplane_notcurses
 //W# ncplane_notcurses_const
 //W  ncplane_off_styles
@@ -92,15 +93,16 @@
 //W  ncplane_set_fg_palindex
 //W  ncplane_set_fg_rgb
 //W  ncplane_set_fg_rgb8
+//W  ncplane_set_name
 //W  ncplane_set_resizecb
 //W  ncplane_set_scrolling
 //W  ncplane_set_styles
-//   ncplane_set_userptr
+//W  ncplane_set_userptr
 //W  ncplane_stain
 //W  ncplane_styles
 //W  ncplane_translate
 //W  ncplane_translate_abs
-//   ncplane_userptr
+//W  ncplane_userptr
 //   ncplane_vline_interp
 //W  ncplane_x
 //W  ncplane_y
@@ -185,14 +187,23 @@
 //   ncplane_putwstr_yx
 //   ncplane_vprintf
 
+mod handle;
 pub(crate) mod helpers;
+mod hit;
 mod methods;
 pub(crate) mod options;
 pub(crate) mod reimplemented;
 #[cfg(test)]
 pub(crate) mod test;
+mod traversal;
+pub(crate) mod user_data;
+mod writer;
 
+pub use handle::NcPlaneHandle;
+pub use hit::NcHit;
 pub use options::{NcPlaneFlags, NcPlaneOptions, NcPlaneOptionsBuilder};
+pub use traversal::{NcPlaneIter, NcPlaneIterMut};
+pub use writer::NcPlaneWriter;
 
 // NcPlane
 //
@@ -1,8 +1,9 @@
 //!
 
-use crate::{c_api, NcAlign, NcPlaneFlags, NcPlaneOptions, NcResizeCb};
+use crate::{c_api, rstring, NcAlign, NcPlaneFlags, NcPlaneOptions, NcResizeCb};
 
 use std::{
+    ffi::CString,
     fmt,
     ptr::{null, null_mut},
 };
@@ -23,12 +24,9 @@ pub struct NcPlaneOptionsBuilder {
     pub(crate) x: i32,
     pub(crate) rows: u32,
     pub(crate) cols: u32,
-    // TODO:
-    // The void* ‘userptr’ can be retrieved (and reset) later.
-    // pub(crate) userptr: mut* c_void,
-    //
-    // A ‘name’ can be set, used in debugging.
-    // pub(crate) name: String,
+    // The void* ‘userptr’ is left null, since it's used to store the Rust
+    // data attached with `NcPlane::set_user_data`.
+    pub(crate) name: Option<String>,
     pub(crate) resizecb: Option<NcResizeCb>,
     pub(crate) flags: u64,
     pub(crate) margin_b: u32,
@@ -78,6 +76,7 @@ impl Default for NcPlaneOptionsBuilder {
             x: 0,
             rows: 0,
             cols: 0,
+            name: None,
             resizecb: None,
             flags: NcPlaneFlags::Marginalized.into(),
             margin_b: 0,
@@ -95,6 +94,7 @@ impl fmt::Debug for NcPlaneOptionsBuilder {
             .field("x", &self.x)
             .field("rows", &self.rows)
             .field("cols", &self.cols)
+            .field("name", &self.name)
             .field("resizecb", &resizecb_str)
             .field("flags", &self.flags)
             .field("margin_b", &self.margin_b)
@@ -148,20 +148,73 @@ impl NcPlaneOptionsBuilder {
             builder = builder.vscroll(true);
         }
 
-        // TODO: name, userptr
+        // name
+        if !options.name.is_null() {
+            builder = builder.name(rstring![options.name]);
+        }
 
         builder
     }
 
+    /// Finishes the building and calls `f` with the [`NcPlaneOptions`],
+    /// including the name, returning its result.
+    ///
+    /// The C string of the name lives until `f` returns, which is enough for
+    /// creating the plane, since notcurses makes its own copy.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use libnotcurses_sys::*;
+    /// # fn main() -> NcResult<()> {
+    /// # let nc = NcContext::new()?;
+    /// let pile = nc.new_pile_sized(0, 0, 4, 10)?;
+    /// let status = NcPlaneOptions::builder()
+    ///     .name("status")
+    ///     .rows_cols(1, 10)
+    ///     .build_with(|options| pile.new_child(options))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn build_with<R>(self, f: impl FnOnce(&NcPlaneOptions) -> R) -> R {
+        // the name was already cut at the first nul character
+        let name = self
+            .name
+            .as_ref()
+            .map(|name| CString::new(name.as_str()).unwrap_or_default());
+        let mut options = self.build_unnamed();
+        if let Some(name) = &name {
+            options.name = name.as_ptr();
+        }
+        f(&options)
+    }
+
     /// Finishes the building and returns [`NcPlaneOptions`].
+    ///
+    /// The options can't include the name, since they can't own its C string.
+    /// Use [`build_with`] in order to create a named plane.
+    ///
+    /// # Panics
+    /// In debug builds, if a [`name`] was set, since it would be lost.
+    ///
+    /// [`build_with`]: NcPlaneOptionsBuilder#method.build_with
+    /// [`name`]: NcPlaneOptionsBuilder#method.name
     pub fn build(self) -> NcPlaneOptions {
+        debug_assert![
+            self.name.is_none(),
+            "NcPlaneOptionsBuilder.build(): the name is only used by build_with()"
+        ];
+        self.build_unnamed()
+    }
+
+    /// Returns the [`NcPlaneOptions`] without the name.
+    fn build_unnamed(self) -> NcPlaneOptions {
         NcPlaneOptions {
             y: self.y,
             x: self.x,
             rows: self.rows,
             cols: self.cols,
-            userptr: null_mut(), // TODO
-            name: null(),        // TODO
+            userptr: null_mut(),
+            name: null(),
             resizecb: c_api::ncresizecb_to_c(self.resizecb),
             flags: self.flags,
             margin_b: self.margin_b,
@@ -384,4 +437,18 @@ impl NcPlaneOptionsBuilder {
         self.resizecb = callback;
         self
     }
+
+    /// Sets the name of the plane, shown in the debugging dumps.
+    ///
+    /// The name ends at the first nul character, if any. It's only used
+    /// by [`build_with`], and [`build`] panics in debug builds if it's set.
+    ///
+    /// Default: *None*.
+    ///
+    /// [`build_with`]: NcPlaneOptionsBuilder#method.build_with
+    /// [`build`]: NcPlaneOptionsBuilder#method.build
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.split('\0').next().unwrap_or_default().to_owned());
+        self
+    }
 }
@@ -1,4 +1,284 @@
 //! Test `NcPlane` methods and associated functions.
 
-// use crate::NcPlane;
-// use serial_test::serial;
+use serial_test::serial;
+
+use crate::{notcurses::helpers::with_test_pile, NcStyle};
+
+#[test]
+#[serial]
+fn ncplanehandle_family() -> crate::NcResult<()> {
+    with_test_pile(10, 20, |_, pile| {
+        let child = pile.new_child_sized(1, 1, 4, 4)?;
+        let grandchild = child.new_child_sized(0, 0, 2, 2)?;
+        assert_eq![(2, 2), grandchild.dim_yx()];
+        grandchild.destroy()
+    })
+}
+
+#[test]
+#[serial]
+fn ncplane_contents_grid() -> crate::NcResult<()> {
+    with_test_pile(3, 6, |_, pile| {
+        pile.set_styles(NcStyle::Bold);
+        pile.putstr_yx(Some(1), Some(1), "ab字")?;
+
+        let grid = pile.contents_grid(Some(1), Some(0), Some(1), None)?;
+        assert_eq![(1, 6), grid.dim_yx()];
+        assert_eq![" ab字 ", grid.row_text(0).unwrap()];
+        let a = grid.get(0, 1).unwrap();
+        assert_eq![("a", NcStyle::Bold), (a.egc.as_str(), a.style)];
+        assert![grid.get(0, 3).unwrap().wide_left_p()];
+        assert![grid.get(0, 4).unwrap().wide_right_p()];
+
+        assert![pile.contents_grid(Some(3), Some(0), None, None).is_err()];
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn ncplane_diff_overlay() -> crate::NcResult<()> {
+    with_test_pile(2, 4, |nc, old| {
+        let mut new = nc.new_pile_sized(0, 0, 2, 4)?;
+        old.putstr_yx(Some(0), Some(0), "abcd")?;
+        new.putstr_yx(Some(0), Some(0), "abXd")?;
+
+        let diff = old.diff(&mut new)?;
+        assert_eq![1, diff.len()];
+        assert_eq![(0, 2), (diff.changes()[0].y, diff.changes()[0].x)];
+
+        let mut overlay = diff.overlay(&new, 0, 0, 0xff0000)?;
+        let grid = overlay.contents_grid(Some(0), Some(0), None, None)?;
+        assert_eq!["X", grid.get(0, 2).unwrap().egc];
+        assert_eq![0xff0000, grid.get(0, 2).unwrap().channels.bg_rgb().0];
+        assert![grid.get(0, 0).unwrap().egc.is_empty()];
+        overlay.destroy()?;
+        new.family_destroy()
+    })
+}
+
+#[test]
+#[serial]
+fn ncplane_writer() -> crate::NcResult<()> {
+    use std::{fmt::Write as _, io};
+
+    with_test_pile(2, 12, |_, pile| {
+        pile.set_styles(NcStyle::Italic);
+        let channels = pile.channels();
+        {
+            let mut w = pile.styled(NcStyle::Bold).fg(0x00ff00);
+            write!(w, "ok:{}", 1).unwrap();
+            assert_eq![NcStyle::Bold | NcStyle::Italic, w.styles()];
+        }
+        assert_eq![NcStyle::Italic, pile.styles()];
+        assert_eq![channels, pile.channels()];
+
+        // the multibyte character is split between writes
+        let mut w = pile.writer();
+        io::Write::write_all(&mut w, &"·é".as_bytes()[..3]).unwrap();
+        io::Write::write_all(&mut w, &"·é".as_bytes()[3..]).unwrap();
+        drop(w);
+
+        let grid = pile.contents_grid(Some(0), Some(0), None, None)?;
+        assert_eq!["ok:1·é", grid.row_text(0).unwrap().trim_end()];
+        assert_eq![
+            NcStyle::Bold | NcStyle::Italic,
+            grid.get(0, 0).unwrap().style
+        ];
+        assert_eq![0x00ff00, grid.get(0, 0).unwrap().channels.fg_rgb().0];
+        assert_eq![NcStyle::Italic, grid.get(0, 4).unwrap().style];
+
+        // the fragments of wrapped text are laid out together
+        pile.cursor_move_yx(1, 0)?;
+        write!(
+            pile.writer().wrap(crate::NcAlign::Center),
+            "{} {}",
+            "ab",
+            "cd"
+        )
+        .unwrap();
+        let grid = pile.contents_grid(Some(1), Some(0), None, None)?;
+        assert_eq!["   ab cd", grid.row_text(0).unwrap().trim_end()];
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn ncplane_putmarkup() -> crate::NcResult<()> {
+    with_test_pile(3, 10, |_, pile| {
+        let rows = pile.putmarkup(Some(0), crate::NcAlign::Right, "[b red]Error:[/] not found")?;
+        assert_eq![2, rows];
+        assert_eq![NcStyle::None, pile.styles()];
+
+        let grid = pile.contents_grid(Some(0), Some(0), None, None)?;
+        assert_eq!["Error: not", grid.row_text(0).unwrap()];
+        assert_eq!["     found", grid.row_text(1).unwrap()];
+        let e = grid.get(0, 0).unwrap();
+        assert_eq![(NcStyle::Bold, 0xff0000), (e.style, e.channels.fg_rgb().0)];
+        assert![pile
+            .putmarkup(Some(2), crate::NcAlign::Left, "too many words")
+            .is_err()];
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn ncplane_traversal() -> crate::NcResult<()> {
+    // the planes are told apart by their number of rows
+    with_test_pile(1, 10, |_, pile| {
+        let child1 = pile.new_child_sized(0, 0, 2, 1)?;
+        let child2 = pile.new_child_sized(0, 0, 3, 1)?;
+        let grandchild = child1.new_child_sized(0, 0, 4, 1)?;
+        let rows = |planes: crate::NcPlaneIter| planes.map(|p| p.dim_y()).collect::<Vec<_>>();
+
+        // new planes are placed at the top
+        assert_eq![vec![4, 3, 2, 1], rows(pile.pile_iter())];
+        assert_eq![vec![1, 2, 3, 4], rows(grandchild.pile_iter().rev())];
+        assert_eq![vec![3, 2], rows(pile.children())];
+        assert_eq![vec![4], rows(child1.children())];
+        assert_eq![0, grandchild.children().len()];
+        assert_eq![vec![3, 2, 4], rows(pile.descendants())];
+
+        drop(grandchild);
+        drop((child1, child2));
+        // SAFETY: no other handles exist, and the planes aren't destroyed
+        for plane in unsafe { pile.as_plane_mut().descendants_mut() } {
+            plane.move_yx(1, 1)?;
+        }
+        assert![pile.descendants().all(|p| p.yx() == (1, 1))];
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn ncplane_hit_test() -> crate::NcResult<()> {
+    use crate::{NcAlpha, NcChannels};
+
+    with_test_pile(4, 10, |_, pile| {
+        let mut transparent = NcChannels::with_default();
+        transparent.set_bg_alpha(NcAlpha::Transparent)?;
+        let mut child = pile.new_child_sized(1, 1, 2, 4)?;
+        child.set_base("", NcStyle::None, transparent)?;
+        child.putstr_yx(Some(0), Some(0), "x")?;
+        drop(child);
+
+        // the child is only hit where it has a glyph
+        let dims = |hit: Option<crate::NcHit>| hit.map(|h| (h.plane.dim_yx(), h.y, h.x));
+        assert_eq![Some(((2, 4), 0, 0)), dims(pile.hit_test(1, 1))];
+        assert_eq![Some(((4, 10), 2, 2)), dims(pile.hit_test(2, 2))];
+        assert_eq![None, dims(pile.hit_test(4, 0))];
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn ncplane_names() -> crate::NcResult<()> {
+    with_test_pile(4, 10, |_, pile| {
+        assert_eq![None, pile.name()];
+
+        let mut child = crate::NcPlaneOptions::builder()
+            .name("status")
+            .rows_cols(1, 10)
+            .build_with(|options| pile.new_child(options))?;
+        assert_eq![Some("status".to_owned()), child.name()];
+
+        child.set_name(Some("footer"))?;
+        drop(child);
+        assert_eq![
+            Some((1, 10)),
+            pile.find_by_name("footer").map(|p| p.dim_yx())
+        ];
+        assert![pile.find_by_name("status").is_none()];
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn ncplane_user_data() -> crate::NcResult<()> {
+    use std::rc::Rc;
+
+    let dropped = Rc::new(());
+    with_test_pile(4, 4, |_, pile| {
+        let mut child = pile.new_child_sized(0, 0, 2, 2)?;
+        assert_eq![None, child.user_data::<u32>()];
+
+        child.set_user_data(5_u32);
+        assert_eq![None, child.user_data::<i32>()];
+        *child.user_data_mut::<u32>().unwrap() += 1;
+        assert_eq![Some(&6), child.user_data::<u32>()];
+        assert_eq![Some(6), child.take_user_data::<u32>()];
+        assert_eq![None, child.user_data::<u32>()];
+
+        // the data is dropped along with the family
+        child.set_user_data(Rc::clone(&dropped));
+        assert_eq![2, Rc::strong_count(&dropped)];
+        Ok(())
+    })?;
+    assert_eq![1, Rc::strong_count(&dropped)];
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn ncplane_on_resize() -> crate::NcResult<()> {
+    use crate::NcResizeStrategy;
+    use std::{cell::Cell, rc::Rc};
+
+    with_test_pile(10, 20, |_, pile| {
+        let calls = Rc::new(Cell::new(0));
+        {
+            let mut counted = pile.new_child_sized(0, 0, 1, 1)?;
+            let count = Rc::clone(&calls);
+            // SAFETY: the callback doesn't destroy the plane
+            unsafe { counted.as_plane_mut() }.on_resize(move |_| {
+                count.set(count.get() + 1);
+                Ok(())
+            });
+            let mut half = pile.new_child_sized(0, 0, 1, 1)?;
+            half.set_resize_strategy(NcResizeStrategy::PercentOfParent { rows: 50, cols: 25 });
+            let mut wide = pile.new_child_sized(0, 0, 1, 4)?;
+            wide.set_resize_strategy(NcResizeStrategy::KeepAspect);
+        }
+
+        // resizing a plane calls the resize callbacks of its children
+        pile.resize_simple(8, 40)?;
+        assert_eq![1, calls.get()];
+        let dims: Vec<_> = pile.children().map(|p| p.dim_yx()).collect();
+        assert_eq![vec![(8, 32), (4, 10), (1, 1)], dims];
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn ncplane_split() -> crate::NcResult<()> {
+    use crate::{NcConstraint::*, NcSplit};
+
+    with_test_pile(10, 20, |_, pile| {
+        let planes = NcSplit::rows(&[Fixed(1), Fill(1), Fixed(2)]).apply(pile)?;
+        assert_eq![
+            vec![((0, 0), (1, 20)), ((1, 0), (7, 20)), ((8, 0), (2, 20))],
+            planes
+                .iter()
+                .map(|p| (p.yx(), p.dim_yx()))
+                .collect::<Vec<_>>()
+        ];
+
+        // the areas are solved again when the parent is resized
+        drop(planes);
+        pile.resize_simple(12, 30)?;
+        assert_eq![
+            vec![((0, 0), (1, 30)), ((1, 0), (9, 30)), ((10, 0), (2, 30))],
+            pile.children()
+                .rev()
+                .map(|p| (p.yx(), p.dim_yx()))
+                .collect::<Vec<_>>()
+        ];
+        Ok(())
+    })
+}
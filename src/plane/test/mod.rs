@@ -1,6 +1,6 @@
 //! `NcPlane` tests.
 
-#[cfg(test)]
+#[cfg(all(test, unix))]
 mod methods;
 
 #[cfg(test)]
@@ -0,0 +1,233 @@
+//! `NcPlaneIter` & `NcPlaneIterMut`
+
+use core::marker::PhantomData;
+use std::vec::IntoIter;
+
+use crate::{c_api, NcPlane};
+
+/// An iterator over shared references to [`NcPlane`]s.
+///
+/// The planes are collected when it's created, so the planes created,
+/// moved or reparented afterwards don't change the iteration.
+///
+/// It can be reversed with [`rev`][Iterator#method.rev].
+///
+/// # Example
+/// ```no_run
+/// # use libnotcurses_sys::*;
+/// fn print_pile(plane: &NcPlane) {
+///     for (z, plane) in plane.pile_iter().enumerate() {
+///         println!("{}: {:?} at {:?}", z, plane.dim_yx(), plane.yx());
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct NcPlaneIter<'plane> {
+    planes: IntoIter<*mut NcPlane>,
+    _plane: PhantomData<&'plane NcPlane>,
+}
+
+/// An iterator over exclusive references to [`NcPlane`]s.
+///
+/// The planes are collected when it's created, so the planes created,
+/// moved or reparented afterwards don't change the iteration.
+///
+/// It's returned by unsafe methods, since the planes must not be destroyed
+/// while iterating, nor be referenced from elsewhere.
+///
+/// It can be reversed with [`rev`][Iterator#method.rev].
+#[derive(Debug)]
+pub struct NcPlaneIterMut<'plane> {
+    planes: IntoIter<*mut NcPlane>,
+    _plane: PhantomData<&'plane mut NcPlane>,
+}
+
+/// # `NcPlane` traversal
+impl NcPlane {
+    /// Returns an iterator over all the planes of the pile of this `NcPlane`,
+    /// from the top to the bottom of the z-axis, including itself.
+    ///
+    /// *(No equivalent C style function)*
+    pub fn pile_iter(&self) -> NcPlaneIter<'_> {
+        NcPlaneIter::new(self.pile_ptrs())
+    }
+
+    /// Returns an iterator over the children of this `NcPlane`, from the top
+    /// to the bottom of the z-axis.
+    ///
+    /// *(No equivalent C style function)*
+    pub fn children(&self) -> NcPlaneIter<'_> {
+        NcPlaneIter::new(self.children_ptrs())
+    }
+
+    /// Returns an iterator over all the descendants of this `NcPlane`,
+    /// depth-first, with each plane followed by its own descendants,
+    /// and the siblings from the top to the bottom of the z-axis.
+    ///
+    /// *(No equivalent C style function)*
+    pub fn descendants(&self) -> NcPlaneIter<'_> {
+        NcPlaneIter::new(self.descendants_ptrs())
+    }
+
+    /// Returns an iterator over all the planes of the pile of this `NcPlane`,
+    /// from the top to the bottom of the z-axis, including itself,
+    /// as exclusive references.
+    ///
+    /// # Safety
+    /// You must be careful not to end up with multiple exclusive references
+    /// to the same plane, since the pile can include planes referenced from
+    /// elsewhere (e.g. by an [`NcPlaneHandle`][crate::NcPlaneHandle]).
+    /// No plane may be destroyed while iterating.
+    ///
+    /// *(No equivalent C style function)*
+    pub unsafe fn pile_iter_mut(&mut self) -> NcPlaneIterMut<'_> {
+        NcPlaneIterMut::new(self.pile_ptrs())
+    }
+
+    /// Returns an iterator over the children of this `NcPlane`, from the top
+    /// to the bottom of the z-axis, as exclusive references.
+    ///
+    /// # Safety
+    /// You must be careful not to end up with multiple exclusive references
+    /// to the same plane. No plane may be destroyed while iterating.
+    ///
+    /// *(No equivalent C style function)*
+    pub unsafe fn children_mut(&mut self) -> NcPlaneIterMut<'_> {
+        NcPlaneIterMut::new(self.children_ptrs())
+    }
+
+    /// Returns an iterator over all the descendants of this `NcPlane`,
+    /// depth-first, as exclusive references.
+    ///
+    /// # Safety
+    /// You must be careful not to end up with multiple exclusive references
+    /// to the same plane. No plane may be destroyed while iterating, since
+    /// the descendants of a plane come after it.
+    ///
+    /// *(No equivalent C style function)*
+    pub unsafe fn descendants_mut(&mut self) -> NcPlaneIterMut<'_> {
+        NcPlaneIterMut::new(self.descendants_ptrs())
+    }
+
+    /// Returns the topmost plane of the pile of this `NcPlane` with the
+    /// provided `name`, or `None` if there's none.
+    ///
+    /// *(No equivalent C style function)*
+    pub fn find_by_name(&self, name: &str) -> Option<&NcPlane> {
+        self.pile_iter()
+            .find(|plane| plane.name().as_deref() == Some(name))
+    }
+
+    /// Returns the topmost plane of the pile of this `NcPlane` with the
+    /// provided `name`, as an exclusive reference, or `None` if there's none.
+    ///
+    /// # Safety
+    /// You must be careful not to end up with multiple exclusive references
+    /// to the same plane, like with [`pile_iter_mut`][NcPlane#method.pile_iter_mut].
+    ///
+    /// *(No equivalent C style function)*
+    pub unsafe fn find_by_name_mut(&mut self, name: &str) -> Option<&mut NcPlane> {
+        self.pile_iter_mut()
+            .find(|plane| plane.name().as_deref() == Some(name))
+    }
+
+    /// Returns the planes of the pile, from the top to the bottom.
+    fn pile_ptrs(&self) -> Vec<*mut NcPlane> {
+        let mut planes = vec![];
+        let mut plane = unsafe { c_api::ncpile_top(self as *const _ as *mut _) };
+        while !plane.is_null() {
+            planes.push(plane);
+            plane = unsafe { c_api::ncplane_below(plane) };
+        }
+        planes
+    }
+
+    /// Returns the children, from the top to the bottom.
+    fn children_ptrs(&self) -> Vec<*mut NcPlane> {
+        children_of(self, &self.pile_ptrs()).collect()
+    }
+
+    /// Returns the descendants, depth-first.
+    fn descendants_ptrs(&self) -> Vec<*mut NcPlane> {
+        let pile = self.pile_ptrs();
+        let mut descendants = vec![];
+        push_descendants(self, &pile, &mut descendants);
+        descendants
+    }
+}
+
+/// Returns the planes of the `pile` that are children of `parent`.
+fn children_of<'a>(
+    parent: *const NcPlane,
+    pile: &'a [*mut NcPlane],
+) -> impl Iterator<Item = *mut NcPlane> + 'a {
+    pile.iter().copied().filter(move |&plane| {
+        // the root planes are their own parents
+        plane as *const _ != parent && unsafe { c_api::ncplane_parent_const(plane) } == parent
+    })
+}
+
+/// Pushes the descendants of `parent` in the `pile`, depth-first.
+fn push_descendants(parent: *const NcPlane, pile: &[*mut NcPlane], out: &mut Vec<*mut NcPlane>) {
+    for child in children_of(parent, pile) {
+        out.push(child);
+        push_descendants(child, pile, out);
+    }
+}
+
+impl<'plane> NcPlaneIter<'plane> {
+    fn new(planes: Vec<*mut NcPlane>) -> Self {
+        Self { planes: planes.into_iter(), _plane: PhantomData }
+    }
+}
+
+impl<'plane> NcPlaneIterMut<'plane> {
+    fn new(planes: Vec<*mut NcPlane>) -> Self {
+        Self { planes: planes.into_iter(), _plane: PhantomData }
+    }
+}
+
+mod std_impls {
+    use super::{NcPlaneIter, NcPlaneIterMut};
+    use crate::NcPlane;
+
+    impl<'plane> Iterator for NcPlaneIter<'plane> {
+        type Item = &'plane NcPlane;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.planes.next().map(|plane| unsafe { &*plane })
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            self.planes.size_hint()
+        }
+    }
+
+    impl<'plane> DoubleEndedIterator for NcPlaneIter<'plane> {
+        fn next_back(&mut self) -> Option<Self::Item> {
+            self.planes.next_back().map(|plane| unsafe { &*plane })
+        }
+    }
+
+    impl<'plane> ExactSizeIterator for NcPlaneIter<'plane> {}
+
+    impl<'plane> Iterator for NcPlaneIterMut<'plane> {
+        type Item = &'plane mut NcPlane;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.planes.next().map(|plane| unsafe { &mut *plane })
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            self.planes.size_hint()
+        }
+    }
+
+    impl<'plane> DoubleEndedIterator for NcPlaneIterMut<'plane> {
+        fn next_back(&mut self) -> Option<Self::Item> {
+            self.planes.next_back().map(|plane| unsafe { &mut *plane })
+        }
+    }
+
+    impl<'plane> ExactSizeIterator for NcPlaneIterMut<'plane> {}
+}
@@ -0,0 +1,172 @@
+//! `NcPlane` user data
+//
+// The Rust data of a plane, its user data and its resize closure, is boxed
+// in a `PlaneData`, pointed to by its C `userptr`. The boxes are also kept
+// in a registry indexed by the address of their plane, which owns them:
+//
+// - a `userptr` is only trusted if the registry has it for that plane,
+//   so one set from C is never mistaken for Rust data.
+// - the data of the planes destroyed without going through this crate
+//   (e.g. by a widget) is still freed when its context stops.
+
+use core::{any::Any, ptr::null_mut};
+use std::{
+    collections::BTreeMap,
+    ffi::c_void,
+    sync::{
+        atomic::{AtomicPtr, Ordering},
+        Mutex, MutexGuard, Once,
+    },
+};
+
+use crate::{c_api, resizecb::NcResizeClosure, Nc, NcPlane};
+
+/// The Rust data attached to a plane.
+#[derive(Default)]
+pub(crate) struct PlaneData {
+    pub(crate) user: Option<Box<dyn Any>>,
+    pub(crate) resize: Option<NcResizeClosure>,
+}
+
+/// An entry of the registry: the address of the data,
+/// and of the context of its plane.
+type Entry = (usize, usize);
+
+/// Returns the locked registry of the planes with data, by plane address.
+fn registry() -> MutexGuard<'static, BTreeMap<usize, Entry>> {
+    static INIT: Once = Once::new();
+    static REGISTRY: AtomicPtr<Mutex<BTreeMap<usize, Entry>>> = AtomicPtr::new(null_mut());
+
+    INIT.call_once(|| {
+        let registry = Box::new(Mutex::new(BTreeMap::new()));
+        REGISTRY.store(Box::into_raw(registry), Ordering::Release);
+    });
+    // SAFETY: REGISTRY is set just once, and never freed.
+    let registry = unsafe { &*REGISTRY.load(Ordering::Acquire) };
+    registry.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Returns the data of the `plane`, if it has any.
+pub(crate) fn plane_data(plane: *const NcPlane) -> Option<*mut PlaneData> {
+    let userptr = unsafe { c_api::ncplane_userptr(plane as *mut _) } as usize;
+    match registry().get(&(plane as usize)) {
+        Some(&(data, _)) if data == userptr && userptr != 0 => Some(data as *mut PlaneData),
+        _ => None,
+    }
+}
+
+/// Returns the data of the `plane`, attaching empty data if it has none.
+pub(crate) fn plane_data_or_default(plane: *mut NcPlane) -> *mut PlaneData {
+    if let Some(data) = plane_data(plane) {
+        return data;
+    }
+    let data = Box::into_raw(Box::new(PlaneData::default()));
+    let nc = unsafe { c_api::ncplane_notcurses(plane) };
+    // an entry left by a destroyed plane that had the same address
+    let stale = registry().insert(plane as usize, (data as usize, nc as usize));
+    unsafe { c_api::ncplane_set_userptr(plane, data as *mut c_void) };
+    drop_data(stale.into_iter());
+    data
+}
+
+/// Frees the data of the `plane`, before destroying it,
+/// unless it's the standard plane, which can't be destroyed.
+pub(crate) fn free_plane_data(plane: *mut NcPlane) {
+    let stdplane = unsafe { c_api::notcurses_stdplane_const(c_api::ncplane_notcurses(plane)) };
+    if plane as *const _ == stdplane {
+        return;
+    }
+    if plane_data(plane).is_some() {
+        unsafe { c_api::ncplane_set_userptr(plane, null_mut()) };
+    }
+    let entry = registry().remove(&(plane as usize));
+    drop_data(entry.into_iter());
+}
+
+/// Frees the data of the `plane` and of all its descendants,
+/// before destroying its family.
+pub(crate) fn free_family_data(plane: &NcPlane) {
+    let family: Vec<*mut NcPlane> = core::iter::once(plane)
+        .chain(plane.descendants())
+        .map(|p| p as *const _ as *mut _)
+        .collect();
+    for plane in family {
+        free_plane_data(plane);
+    }
+}
+
+/// Frees the data of all the planes of the context `nc`, except `keep`.
+///
+/// Must be called when the planes are destroyed by the context.
+pub(crate) fn free_nc_data(nc: *const Nc, keep: *const NcPlane) {
+    let entries: Vec<Entry> = {
+        let mut registry = registry();
+        let planes: Vec<usize> = registry
+            .iter()
+            .filter(|(&plane, &(_, n))| n == nc as usize && plane != keep as usize)
+            .map(|(&plane, _)| plane)
+            .collect();
+        planes
+            .iter()
+            .filter_map(|plane| registry.remove(plane))
+            .collect()
+    };
+    drop_data(entries.into_iter());
+}
+
+/// Drops the data of the `entries`, once they are out of the registry,
+/// since the drop of the user data could use it again.
+fn drop_data(entries: impl Iterator<Item = Entry>) {
+    for (data, _) in entries {
+        drop(unsafe { Box::from_raw(data as *mut PlaneData) });
+    }
+}
+
+/// # `NcPlane` user data
+impl NcPlane {
+    /// Attaches some Rust `data` to this `NcPlane`, replacing the previous one.
+    ///
+    /// The data is dropped when the plane is destroyed, or when its context
+    /// stops if the plane was destroyed by other means (e.g. by a widget).
+    ///
+    /// It's stored in the C `userptr` of the plane, which must not be set
+    /// by other means while it's being used.
+    ///
+    /// *C style function: [ncplane_set_userptr()][c_api::ncplane_set_userptr].*
+    pub fn set_user_data<T: 'static>(&mut self, data: T) {
+        let plane_data = plane_data_or_default(self);
+        let previous = unsafe { (*plane_data).user.replace(Box::new(data)) };
+        drop(previous);
+    }
+
+    /// Returns a reference to the data attached to this `NcPlane`,
+    /// or `None` if there's none or it's not a `T`.
+    ///
+    /// *C style function: [ncplane_userptr()][c_api::ncplane_userptr].*
+    pub fn user_data<T: 'static>(&self) -> Option<&T> {
+        let data = plane_data(self)?;
+        unsafe { (*data).user.as_deref()?.downcast_ref() }
+    }
+
+    /// Returns an exclusive reference to the data attached to this `NcPlane`,
+    /// or `None` if there's none or it's not a `T`.
+    ///
+    /// *C style function: [ncplane_userptr()][c_api::ncplane_userptr].*
+    pub fn user_data_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        let data = plane_data(self)?;
+        unsafe { (*data).user.as_deref_mut()?.downcast_mut() }
+    }
+
+    /// Detaches the data attached to this `NcPlane` and returns it,
+    /// or returns `None` and leaves it attached if it's not a `T`.
+    ///
+    /// *(No equivalent C style function)*
+    pub fn take_user_data<T: 'static>(&mut self) -> Option<T> {
+        let data = plane_data(self)?;
+        let user = unsafe { &mut (*data).user };
+        if !user.as_deref()?.is::<T>() {
+            return None;
+        }
+        user.take()?.downcast::<T>().ok().map(|data| *data)
+    }
+}
@@ -0,0 +1,280 @@
+//! `NcPlaneWriter`
+
+use core::ptr::null_mut;
+use std::ffi::CString;
+
+use crate::{
+    c_api, error, error::clear_errno, NcAlign, NcChannels, NcPaletteIndex, NcPlane, NcResult,
+    NcRgb, NcStyle,
+};
+
+/// A writer over an [`NcPlane`], putting text at its cursor.
+///
+/// It implements [`fmt::Write`][core::fmt::Write] and [`io::Write`][std::io::Write],
+/// so it can be used with the `write!` and `writeln!` macros.
+///
+/// It's also a scoped style guard: the styles and colors can be changed
+/// while creating it, and the previous ones are restored when it's dropped.
+///
+/// By default the text is written like with [`putstr`][NcPlane#method.putstr],
+/// and it can be [`wrap`][NcPlaneWriter#method.wrap]ped instead, like with
+/// [`puttext`][NcPlane#method.puttext]. Wrapped text is kept until a newline,
+/// a [`flush_wrapped`][NcPlaneWriter#method.flush_wrapped] or the drop of the
+/// writer, so that the fragments of a `write!` are laid out together.
+///
+/// It dereferences to the [`NcPlane`], and other writers can be nested with
+/// its own [`writer`][NcPlaneWriter#method.writer] and
+/// [`styled`][NcPlaneWriter#method.styled] methods.
+///
+/// # Example
+/// ```no_run
+/// # use libnotcurses_sys::*;
+/// use std::fmt::Write;
+///
+/// # fn main() -> NcResult<()> {
+/// # let nc = unsafe { Nc::new()? };
+/// # let plane = unsafe { nc.stdplane() };
+/// {
+///     let mut w = plane.styled(NcStyle::Bold).fg(0xff0000);
+///     write!(w, "Error: ").unwrap();
+/// }
+/// // the style and colors of the plane are restored here
+/// writeln!(plane.writer(), "{} files not found", 3).unwrap();
+/// # unsafe { nc.stop()? };
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct NcPlaneWriter<'plane> {
+    plane: &'plane mut NcPlane,
+    wrap: Option<NcAlign>,
+    styles: NcStyle,
+    channels: NcChannels,
+    // the bytes of an incomplete UTF-8 sequence received by `io::Write`
+    pending: Vec<u8>,
+    // the text waiting to be wrapped
+    wrapped: String,
+}
+
+/// # `NcPlaneWriter` constructors
+impl<'plane> NcPlaneWriter<'plane> {
+    /// New writer over the `plane`, saving its current styles and colors.
+    pub fn new(plane: &'plane mut NcPlane) -> Self {
+        let styles = plane.styles();
+        let channels = plane.channels();
+        Self { plane, wrap: None, styles, channels, pending: vec![], wrapped: String::new() }
+    }
+
+    /// Adds the `styles` to the ones of the plane.
+    pub fn on(mut self, styles: NcStyle) -> Self {
+        self.plane.on_styles(styles);
+        self
+    }
+
+    /// Removes the `styles` from the ones of the plane.
+    pub fn off(mut self, styles: NcStyle) -> Self {
+        self.plane.off_styles(styles);
+        self
+    }
+
+    /// Sets the foreground [`NcRgb`].
+    pub fn fg<RGB: Into<NcRgb>>(mut self, rgb: RGB) -> Self {
+        self.plane.set_fg_rgb(rgb);
+        self
+    }
+
+    /// Sets the background [`NcRgb`].
+    pub fn bg<RGB: Into<NcRgb>>(mut self, rgb: RGB) -> Self {
+        self.plane.set_bg_rgb(rgb);
+        self
+    }
+
+    /// Sets the foreground [`NcPaletteIndex`].
+    pub fn fg_palindex(mut self, palindex: NcPaletteIndex) -> Self {
+        self.plane.set_fg_palindex(palindex);
+        self
+    }
+
+    /// Sets the background [`NcPaletteIndex`].
+    pub fn bg_palindex(mut self, palindex: NcPaletteIndex) -> Self {
+        self.plane.set_bg_palindex(palindex);
+        self
+    }
+
+    /// Uses the default foreground color.
+    pub fn fg_default(mut self) -> Self {
+        self.plane.set_fg_default();
+        self
+    }
+
+    /// Uses the default background color.
+    pub fn bg_default(mut self) -> Self {
+        self.plane.set_bg_default();
+        self
+    }
+
+    /// Wraps the text at word boundaries, with the provided [`NcAlign`]ment,
+    /// like [`puttext`][NcPlane#method.puttext] does, starting at the
+    /// current row.
+    ///
+    /// The text is put up to its last newline, and the rest is kept until
+    /// the next newline, a [`flush_wrapped`][NcPlaneWriter#method.flush_wrapped]
+    /// or the drop of the writer.
+    pub fn wrap(mut self, align: NcAlign) -> Self {
+        self.wrap = Some(align);
+        self
+    }
+}
+
+/// # `NcPlaneWriter` methods
+impl<'plane> NcPlaneWriter<'plane> {
+    /// Returns a nested writer over the same plane, which restores the
+    /// styles and colors of this one when it's dropped.
+    ///
+    /// The text waiting to be wrapped by this one is put first.
+    pub fn writer(&mut self) -> NcPlaneWriter<'_> {
+        let _ = self.flush_wrapped();
+        NcPlaneWriter::new(self.plane)
+    }
+
+    /// Returns a nested writer over the same plane, with the `styles` added
+    /// until it's dropped.
+    ///
+    /// The text waiting to be wrapped by this one is put first.
+    pub fn styled(&mut self, styles: NcStyle) -> NcPlaneWriter<'_> {
+        self.writer().on(styles)
+    }
+
+    /// Writes a `string` at the cursor, with the current style and colors,
+    /// returning the number of columns advanced.
+    ///
+    /// When wrapping, only the text up to the last newline is put, and the
+    /// columns returned are the ones of that text.
+    ///
+    /// *C style function: [ncplane_putstr()][c_api::ncplane_putstr] or
+    /// [ncplane_puttext()][c_api::ncplane_puttext] when wrapping.*
+    pub fn put(&mut self, string: &str) -> NcResult<u32> {
+        if self.wrap.is_none() {
+            return self.plane.putstr(string);
+        }
+        self.wrapped.push_str(string);
+        match self.wrapped.rfind('\n') {
+            Some(i) => {
+                let rest = self.wrapped.split_off(i + 1);
+                let lines = core::mem::replace(&mut self.wrapped, rest);
+                self.put_wrapped(&lines)
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Puts the text waiting to be wrapped, returning the number of columns
+    /// advanced.
+    ///
+    /// *C style function: [ncplane_puttext()][c_api::ncplane_puttext].*
+    pub fn flush_wrapped(&mut self) -> NcResult<u32> {
+        if self.wrapped.is_empty() {
+            return Ok(0);
+        }
+        let text = core::mem::take(&mut self.wrapped);
+        self.put_wrapped(&text)
+    }
+
+    /// Puts the `text` with `ncplane_puttext`, wrapped with the alignment.
+    fn put_wrapped(&mut self, text: &str) -> NcResult<u32> {
+        let align = self.wrap.unwrap_or(NcAlign::Left);
+        let cstring = CString::new(text)?;
+        clear_errno();
+        let res = unsafe {
+            c_api::ncplane_puttext(self.plane, -1, align.into(), cstring.as_ptr(), null_mut())
+        };
+        error![@fn "ncplane_puttext", res, &format!("NcPlaneWriter.put({:?})", text), res as u32]
+    }
+
+    /// Writes the complete UTF-8 sequences of the pending bytes.
+    fn put_pending(&mut self) -> NcResult<()> {
+        let valid = match core::str::from_utf8(&self.pending) {
+            Ok(s) => s.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => {
+                self.pending.clear();
+                return Err(e.into());
+            }
+        };
+        let rest = self.pending.split_off(valid);
+        let complete = core::mem::replace(&mut self.pending, rest);
+        let string = core::str::from_utf8(&complete)?;
+        if !string.is_empty() {
+            self.put(string)?;
+        }
+        Ok(())
+    }
+}
+
+/// # `NcPlane` writers
+impl NcPlane {
+    /// Returns a writer putting text at the cursor of this `NcPlane`.
+    ///
+    /// *(No equivalent C style function)*
+    pub fn writer(&mut self) -> NcPlaneWriter<'_> {
+        NcPlaneWriter::new(self)
+    }
+
+    /// Returns a writer putting text at the cursor of this `NcPlane`,
+    /// with the `styles` added until it's dropped.
+    ///
+    /// *(No equivalent C style function)*
+    pub fn styled(&mut self, styles: NcStyle) -> NcPlaneWriter<'_> {
+        NcPlaneWriter::new(self).on(styles)
+    }
+}
+
+mod std_impls {
+    use super::NcPlaneWriter;
+    use crate::NcPlane;
+    use core::{fmt, ops::Deref};
+    use std::io;
+
+    impl<'plane> Deref for NcPlaneWriter<'plane> {
+        type Target = NcPlane;
+
+        fn deref(&self) -> &NcPlane {
+            self.plane
+        }
+    }
+
+    /// Puts the text waiting to be wrapped, and restores the styles and
+    /// colors the plane had.
+    impl<'plane> Drop for NcPlaneWriter<'plane> {
+        fn drop(&mut self) {
+            let _ = self.flush_wrapped();
+            self.plane.set_styles(self.styles);
+            self.plane.set_channels(self.channels);
+        }
+    }
+
+    impl<'plane> fmt::Write for NcPlaneWriter<'plane> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.put(s).map(|_| ()).map_err(|_| fmt::Error)
+        }
+    }
+
+    /// The bytes must be UTF-8. An incomplete sequence at the end of a write
+    /// is kept until the next one completes it.
+    impl<'plane> io::Write for NcPlaneWriter<'plane> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.pending.extend_from_slice(buf);
+            match self.put_pending() {
+                Ok(()) => Ok(buf.len()),
+                Err(e) => Err(io::Error::new(io::ErrorKind::Other, e)),
+            }
+        }
+
+        /// Puts the text waiting to be wrapped.
+        fn flush(&mut self) -> io::Result<()> {
+            self.flush_wrapped()
+                .map(|_| ())
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+        }
+    }
+}
@@ -1,6 +1,12 @@
 //! `NcResizeCb`
 
-use crate::{c_api::NcResult_i32, NcPlane};
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::{
+    c_api::NcResult_i32,
+    plane::user_data::{plane_data, plane_data_or_default},
+    NcPlane, NcResult,
+};
 
 /// A callback function called when an [`NcPlane`] is resized.
 ///
@@ -8,8 +14,157 @@ use crate::{c_api::NcResult_i32, NcPlane};
 /// - [`NcResizeCbUnsafe`][c_api::NcResizeCbUnsafe]
 /// - [`ncresizecb_to_rust`][c_api::ncresizecb_to_rust]
 /// - [`ncresizecb_to_c`][c_api::ncresizecb_to_c]
+/// - [`NcPlane::on_resize`][NcPlane#method.on_resize], for closures.
 pub type NcResizeCb = fn(&mut NcPlane) -> NcResult_i32;
 
+/// A resize callback that can capture its environment.
+pub(crate) type NcResizeClosure = Box<dyn FnMut(&mut NcPlane) -> NcResult<()>>;
+
+/// A ready-made way of resizing an [`NcPlane`] when its parent is resized.
+///
+/// It's set with [`NcPlane::set_resize_strategy`][NcPlane#method.set_resize_strategy].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NcResizeStrategy {
+    /// Resizes the plane to the size of the visual region.
+    ///
+    /// See [`resize_maximize`][NcPlane#method.resize_maximize].
+    Maximize,
+
+    /// Realigns the plane against its parent, with the alignment
+    /// it was created with.
+    ///
+    /// See [`resize_realign`][NcPlane#method.resize_realign].
+    Realign,
+
+    /// Resizes the plane to its parent, minus the margins
+    /// it was created with.
+    ///
+    /// See [`resize_marginalized`][NcPlane#method.resize_marginalized].
+    Marginalize,
+
+    /// Resizes the plane to the biggest size that fits in its parent from its
+    /// origin, keeping the proportion between the rows and the columns it had
+    /// when the strategy was set.
+    KeepAspect,
+
+    /// Resizes the plane to a percentage of the rows and columns of its
+    /// parent, keeping its origin. It's never smaller than 1×1.
+    PercentOfParent {
+        /// The percentage of the rows of the parent.
+        rows: u32,
+        /// The percentage of the columns of the parent.
+        cols: u32,
+    },
+}
+
+/// # `NcPlane` resize callbacks
+impl NcPlane {
+    /// Sets a `callback` to be called when the parent of this `NcPlane` is
+    /// resized, replacing its current resize callback.
+    ///
+    /// Unlike an [`NcResizeCb`], it can capture its environment. It's kept
+    /// with the plane, and dropped along with its
+    /// [user data][NcPlane#method.set_user_data].
+    ///
+    /// The callback must not destroy the plane.
+    ///
+    /// *(No equivalent C style function)*
+    pub fn on_resize<F>(&mut self, callback: F)
+    where
+        F: FnMut(&mut NcPlane) -> NcResult<()> + 'static,
+    {
+        let data = plane_data_or_default(self);
+        unsafe {
+            (*data).resize = Some(Box::new(callback));
+            crate::c_api::ncplane_set_resizecb(self, Some(resize_trampoline));
+        }
+    }
+
+    /// Sets a ready-made resize `strategy`, replacing the current
+    /// resize callback.
+    ///
+    /// See [`on_resize`][NcPlane#method.on_resize].
+    ///
+    /// *(No equivalent C style function)*
+    pub fn set_resize_strategy(&mut self, strategy: NcResizeStrategy) {
+        match strategy {
+            NcResizeStrategy::Maximize => self.on_resize(NcPlane::resize_maximize),
+            NcResizeStrategy::Realign => self.on_resize(NcPlane::resize_realign),
+            NcResizeStrategy::Marginalize => self.on_resize(NcPlane::resize_marginalized),
+            NcResizeStrategy::KeepAspect => {
+                let (rows, cols) = self.dim_yx();
+                self.on_resize(move |plane| {
+                    let (len_y, len_x) = fit_aspect(available_dim_yx(plane), (rows, cols));
+                    plane.resize_simple(len_y, len_x)
+                });
+            }
+            NcResizeStrategy::PercentOfParent { rows, cols } => self.on_resize(move |plane| {
+                let (parent_y, parent_x) = parent_dim_yx(plane);
+                let percent = |len: u32, pc: u32| (len as u64 * pc as u64 / 100).max(1) as u32;
+                plane.resize_simple(percent(parent_y, rows), percent(parent_x, cols))
+            }),
+        }
+    }
+}
+
+/// Calls the resize closure of the `plane`.
+unsafe extern "C" fn resize_trampoline(plane: *mut NcPlane) -> NcResult_i32 {
+    let mut callback = match plane_data(plane).and_then(|data| (*data).resize.take()) {
+        Some(callback) => callback,
+        None => return crate::c_api::NCRESULT_ERR,
+    };
+    let res = panic::catch_unwind(AssertUnwindSafe(|| callback(&mut *plane)));
+
+    // it's taken out while it runs, so it can set a new one
+    if let Some(data) = plane_data(plane) {
+        if (*data).resize.is_none() {
+            (*data).resize = Some(callback);
+        }
+    }
+    match res {
+        Ok(Ok(())) => crate::c_api::NCRESULT_OK,
+        Ok(Err(_)) => crate::c_api::NCRESULT_ERR,
+        // a panic can't unwind into C
+        Err(_) => std::process::abort(),
+    }
+}
+
+/// Returns the rows and columns of the parent of the `plane`,
+/// or of the standard plane if it's a root plane.
+fn parent_dim_yx(plane: &NcPlane) -> (u32, u32) {
+    let mut parent = unsafe { crate::c_api::ncplane_parent_const(plane) };
+    if parent == plane as *const _ {
+        parent = unsafe {
+            crate::c_api::notcurses_stdplane_const(crate::c_api::ncplane_notcurses_const(plane))
+        };
+    }
+    unsafe { &*parent }.dim_yx()
+}
+
+/// Returns the rows and columns of the parent of the `plane`
+/// from the origin of the `plane`.
+fn available_dim_yx(plane: &NcPlane) -> (u32, u32) {
+    let (parent_y, parent_x) = parent_dim_yx(plane);
+    let (y, x) = plane.yx();
+    (
+        parent_y.saturating_sub(y.max(0) as u32),
+        parent_x.saturating_sub(x.max(0) as u32),
+    )
+}
+
+/// Returns the biggest size that fits in `available`,
+/// with the same proportion as `aspect`.
+fn fit_aspect(available: (u32, u32), aspect: (u32, u32)) -> (u32, u32) {
+    let (avail_y, avail_x) = (available.0 as u64, available.1 as u64);
+    let (rows, cols) = (aspect.0.max(1) as u64, aspect.1.max(1) as u64);
+    let (len_y, len_x) = if avail_y * cols <= avail_x * rows {
+        (avail_y, avail_y * cols / rows)
+    } else {
+        (avail_x * rows / cols, avail_x)
+    };
+    (len_y.max(1) as u32, len_x.max(1) as u32)
+}
+
 pub(crate) mod c_api {
     use super::*;
 
@@ -29,3 +184,15 @@ pub(crate) mod c_api {
         resizecb.map(|cb| unsafe { core::mem::transmute(cb) })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::fit_aspect;
+
+    #[test]
+    fn fit_aspect_ratio() {
+        assert_eq![(10, 20), fit_aspect((10, 40), (1, 2))];
+        assert_eq![(15, 30), fit_aspect((50, 30), (1, 2))];
+        assert_eq![(1, 1), fit_aspect((0, 5), (3, 4))];
+    }
+}
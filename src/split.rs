@@ -0,0 +1,293 @@
+//! `NcSplit`
+
+use crate::{NcPlane, NcPlaneHandle, NcResult};
+
+/// A constraint on the length of an area of an [`NcSplit`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NcConstraint {
+    /// A fixed length.
+    Fixed(u32),
+
+    /// A percentage of the length available to the areas, up to 100.
+    Percent(u32),
+
+    /// At least this length, growing to share the remaining space.
+    Min(u32),
+
+    /// At most this length, growing to share the remaining space.
+    Max(u32),
+
+    /// A share of the remaining space, weighted against the other areas
+    /// that grow, which weigh 1.
+    Fill(u32),
+}
+
+/// The direction in which an [`NcSplit`] divides its parent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NcSplitDirection {
+    /// Into bands of rows, from the top to the bottom.
+    Rows,
+
+    /// Into bands of columns, from the left to the right.
+    Cols,
+}
+
+/// A layout that splits a parent [`NcPlane`] into child planes.
+///
+/// The parent is divided in one direction into areas, each one with an
+/// [`NcConstraint`] on its length, while they take the whole length of the
+/// parent in the other direction. The length of each area is solved like
+/// this:
+///
+/// 1. the [`Fixed`], [`Percent`] and [`Min`] areas get their length.
+/// 2. the remaining space is shared between the [`Min`], [`Max`] and
+///    [`Fill`] areas, by their weight, and without growing the [`Max`]
+///    areas beyond their maximum.
+/// 3. if the constraints need more space than there is, the last areas
+///    are shrunk.
+///
+/// [`apply`][NcSplit#method.apply] creates a child plane for each area,
+/// which moves and resizes itself whenever the parent is resized (e.g. by
+/// its own resize callback). Since planes can't be empty, the areas without
+/// space get a 1×1 plane.
+///
+/// Splits can be nested by applying them to the children of another split.
+///
+/// # Example
+/// ```no_run
+/// # use libnotcurses_sys::*;
+/// # fn main() -> NcResult<()> {
+/// # let nc = NcContext::new()?;
+/// use NcConstraint::*;
+///
+/// let root = nc.new_pile_sized(0, 0, 24, 80)?;
+/// let mut planes = NcSplit::rows(&[Fixed(1), Fill(1), Fixed(1)]).apply(&root)?;
+/// planes[0].putstr("header")?;
+///
+/// let columns = NcSplit::cols(&[Percent(25), Min(20)]).spacing(1);
+/// let body = columns.apply(&planes[1])?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Fixed`]: NcConstraint#variant.Fixed
+/// [`Percent`]: NcConstraint#variant.Percent
+/// [`Min`]: NcConstraint#variant.Min
+/// [`Max`]: NcConstraint#variant.Max
+/// [`Fill`]: NcConstraint#variant.Fill
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NcSplit {
+    direction: NcSplitDirection,
+    constraints: Vec<NcConstraint>,
+    spacing: u32,
+}
+
+/// # Constructors
+impl NcSplit {
+    /// New split in the `direction`, with an area for each of the
+    /// `constraints`.
+    pub fn new(direction: NcSplitDirection, constraints: &[NcConstraint]) -> Self {
+        Self { direction, constraints: constraints.to_vec(), spacing: 0 }
+    }
+
+    /// New split into bands of rows.
+    pub fn rows(constraints: &[NcConstraint]) -> Self {
+        Self::new(NcSplitDirection::Rows, constraints)
+    }
+
+    /// New split into bands of columns.
+    pub fn cols(constraints: &[NcConstraint]) -> Self {
+        Self::new(NcSplitDirection::Cols, constraints)
+    }
+
+    /// Sets the space left between the areas.
+    ///
+    /// Default: *`0`*.
+    pub fn spacing(mut self, spacing: u32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+}
+
+/// # Methods
+impl NcSplit {
+    /// Returns the direction.
+    pub fn direction(&self) -> NcSplitDirection {
+        self.direction
+    }
+
+    /// Returns the constraints of the areas.
+    pub fn constraints(&self) -> &[NcConstraint] {
+        &self.constraints
+    }
+
+    /// Solves the split of a length `len`, returning the offset and the
+    /// length of each area.
+    pub fn solve(&self, len: u32) -> Vec<(u32, u32)> {
+        let count = self.constraints.len() as u32;
+        let avail = len.saturating_sub(self.spacing.saturating_mul(count.saturating_sub(1)));
+
+        let mut lens: Vec<u32> = self
+            .constraints
+            .iter()
+            .map(|c| match *c {
+                NcConstraint::Fixed(len) | NcConstraint::Min(len) => len,
+                NcConstraint::Percent(pc) => (avail as u64 * pc.min(100) as u64 / 100) as u32,
+                NcConstraint::Max(_) | NcConstraint::Fill(_) => 0,
+            })
+            .collect();
+
+        let used = lens.iter().fold(0_u32, |sum, len| sum.saturating_add(*len));
+        if used > avail {
+            let mut left = avail;
+            for len in &mut lens {
+                *len = (*len).min(left);
+                left -= *len;
+            }
+        } else {
+            self.share(&mut lens, avail - used);
+        }
+
+        // the offsets past the end, due to the spacing, are kept at the end
+        let mut offset = 0_u32;
+        lens.into_iter()
+            .map(|area_len| {
+                let area = (offset.min(len), area_len);
+                offset = offset.saturating_add(area_len).saturating_add(self.spacing);
+                area
+            })
+            .collect()
+    }
+
+    /// Returns the `y`, `x`, rows and columns of each area, for a parent
+    /// with `dim_y` rows and `dim_x` columns.
+    pub fn areas(&self, dim_y: u32, dim_x: u32) -> Vec<(u32, u32, u32, u32)> {
+        match self.direction {
+            NcSplitDirection::Rows => self
+                .solve(dim_y)
+                .into_iter()
+                .map(|(y, rows)| (y, 0, rows, dim_x))
+                .collect(),
+            NcSplitDirection::Cols => self
+                .solve(dim_x)
+                .into_iter()
+                .map(|(x, cols)| (0, x, dim_y, cols))
+                .collect(),
+        }
+    }
+
+    /// Creates a child plane of `parent` for each area, and returns handles
+    /// to them, which borrow `parent`.
+    ///
+    /// Each child moves and resizes itself to its area whenever the parent
+    /// is resized, with an [`on_resize`][NcPlane#method.on_resize] callback.
+    ///
+    /// Applying a split again to the same parent creates another set of
+    /// children, over the previous ones. Destroy those first in order to
+    /// replace them.
+    ///
+    /// *(No equivalent C style function)*
+    pub fn apply<'parent>(
+        &self,
+        parent: &'parent NcPlaneHandle<'_>,
+    ) -> NcResult<Vec<NcPlaneHandle<'parent>>> {
+        let (dim_y, dim_x) = parent.dim_yx();
+        let mut planes = vec![];
+        for (i, (y, x, rows, cols)) in self.areas(dim_y, dim_x).into_iter().enumerate() {
+            let mut plane =
+                match parent.new_child_sized(y as i32, x as i32, rows.max(1), cols.max(1)) {
+                    Ok(plane) => plane,
+                    Err(e) => {
+                        for plane in planes {
+                            let _ = plane.destroy();
+                        }
+                        return Err(e);
+                    }
+                };
+            let split = self.clone();
+            plane.plane_mut().on_resize(move |plane| {
+                let (dim_y, dim_x) = plane.parent_const()?.dim_yx();
+                let (y, x, rows, cols) = split.areas(dim_y, dim_x)[i];
+                plane.resize_simple(rows.max(1), cols.max(1))?;
+                plane.move_yx(y as i32, x as i32)
+            });
+            planes.push(plane);
+        }
+        Ok(planes)
+    }
+
+    /// Shares the `remaining` space between the areas that grow.
+    fn share(&self, lens: &mut [u32], mut remaining: u32) {
+        let growth: Vec<(u32, u32)> = self
+            .constraints
+            .iter()
+            .map(|c| match *c {
+                NcConstraint::Min(_) => (1, u32::MAX),
+                NcConstraint::Max(max) => (1, max),
+                NcConstraint::Fill(weight) => (weight, u32::MAX),
+                NcConstraint::Fixed(_) | NcConstraint::Percent(_) => (0, 0),
+            })
+            .collect();
+
+        while remaining > 0 {
+            let open: Vec<usize> = (0..lens.len())
+                .filter(|&i| growth[i].0 > 0 && lens[i] < growth[i].1)
+                .collect();
+            let total: u64 = open.iter().map(|&i| growth[i].0 as u64).sum();
+            if total == 0 {
+                break;
+            }
+            let mut given = 0;
+            for &i in &open {
+                let share = (remaining as u64 * growth[i].0 as u64 / total) as u32;
+                let share = share.min(growth[i].1 - lens[i]);
+                lens[i] += share;
+                given += share;
+            }
+            // the rounding leftovers, one by one
+            if given == 0 {
+                for &i in open.iter().take(remaining as usize) {
+                    lens[i] += 1;
+                    given += 1;
+                }
+            }
+            remaining -= given;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{NcConstraint::*, NcSplit};
+
+    #[test]
+    fn solve_fixed_and_fill() {
+        let split = NcSplit::rows(&[Fixed(1), Fill(1), Fixed(1)]);
+        assert_eq![vec![(0, 1), (1, 8), (9, 1)], split.solve(10)];
+        assert_eq![
+            vec![(0, 0, 1, 20), (1, 0, 8, 20), (9, 0, 1, 20)],
+            split.areas(10, 20)
+        ];
+    }
+
+    #[test]
+    fn solve_percent_min_max() {
+        let split = NcSplit::cols(&[Percent(50), Max(2), Fill(1)]);
+        assert_eq![vec![(0, 5), (5, 2), (7, 3)], split.solve(10)];
+        let split = NcSplit::cols(&[Min(3), Fill(3)]);
+        assert_eq![vec![(0, 5), (5, 6)], split.solve(11)];
+        // more than the whole length is clamped to it
+        let split = NcSplit::cols(&[Percent(150), Fill(1)]);
+        assert_eq![vec![(0, 10), (10, 0)], split.solve(10)];
+    }
+
+    #[test]
+    fn solve_spacing_and_overflow() {
+        let split = NcSplit::rows(&[Fill(1), Fill(1)]).spacing(1);
+        assert_eq![vec![(0, 4), (5, 4)], split.solve(9)];
+        let split = NcSplit::rows(&[Fixed(6), Fixed(6), Fixed(1)]);
+        assert_eq![vec![(0, 6), (6, 4), (10, 0)], split.solve(10)];
+        let split = NcSplit::rows(&[Fill(1), Fill(1), Fill(1)]).spacing(u32::MAX / 2);
+        assert_eq![vec![(0, 0), (10, 0), (10, 0)], split.solve(10)];
+    }
+}
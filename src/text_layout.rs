@@ -0,0 +1,300 @@
+//! `NcTextLayout`
+
+use crate::{
+    unicode::{char_width, GraphemeBreaker},
+    NcAlign, NcError, NcErrorKind, NcMarkup, NcPlane, NcResult, NcSpan,
+};
+
+/// Text broken into lines of a maximum width, ready to be drawn.
+///
+/// The text is broken at the spaces between words, and the words wider than
+/// a line are broken between grapheme clusters, so that combining marks,
+/// flags and emoji sequences are kept whole. Each cluster is as wide as its
+/// first character, which doesn't depend on the locale. The spaces at the
+/// breaking points are dropped, and newlines always start a new line.
+///
+/// Since the layout is measured without a plane, it can be used to know the
+/// size of the plane needed for a text, before creating it.
+///
+/// # Example
+/// ```no_run
+/// # use libnotcurses_sys::*;
+/// # fn main() -> NcResult<()> {
+/// # let nc = unsafe { Nc::new()? };
+/// # let stdplane = unsafe { nc.stdplane() };
+/// let layout = NcTextLayout::new("a paragraph that needs some rows", 12, NcAlign::Center);
+/// assert_eq![(3, 11), layout.dim_yx()];
+///
+/// let plane = NcPlane::new_child_sized(stdplane, 1, 1, layout.rows(), 12)?;
+/// layout.draw(plane, Some(0), 0)?;
+/// # unsafe { nc.stop()? };
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct NcTextLayout {
+    spans: Vec<NcSpan>,
+    width: u32,
+    lines: Vec<NcTextLine>,
+}
+
+/// A line of an [`NcTextLayout`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NcTextLine {
+    /// The column where it starts, relative to the layout, due to the alignment.
+    pub x: u32,
+
+    /// The number of columns it occupies.
+    pub cols: u32,
+
+    /// The fragments of text, with the index of the span of the layout
+    /// they belong to.
+    pub fragments: Vec<(usize, String)>,
+
+    // whether it starts because the previous line was too long
+    wrapped: bool,
+}
+
+/// # Constructors
+impl NcTextLayout {
+    /// New layout of a plain `text`, broken into lines of up to `width`
+    /// columns, aligned horizontally with `align`.
+    pub fn new(text: &str, width: u32, align: NcAlign) -> Self {
+        Self::with_spans(vec![NcSpan::new(text)], width, align)
+    }
+
+    /// New layout of a text with styles and colors.
+    pub fn with_markup(markup: &NcMarkup, width: u32, align: NcAlign) -> Self {
+        Self::with_spans(markup.spans().to_vec(), width, align)
+    }
+
+    /// New layout of some styled `spans`.
+    pub fn with_spans(spans: Vec<NcSpan>, width: u32, align: NcAlign) -> Self {
+        let mut lines = break_lines(&spans, width);
+        for line in &mut lines {
+            line.x = match align {
+                NcAlign::Center => width.saturating_sub(line.cols) / 2,
+                NcAlign::Right => width.saturating_sub(line.cols),
+                NcAlign::Left | NcAlign::Unaligned => 0,
+            };
+        }
+        Self { spans, width, lines }
+    }
+}
+
+/// # Methods
+impl NcTextLayout {
+    /// Returns the lines.
+    pub fn lines(&self) -> &[NcTextLine] {
+        &self.lines
+    }
+
+    /// Returns the styled spans the text is made of.
+    pub fn spans(&self) -> &[NcSpan] {
+        &self.spans
+    }
+
+    /// Returns the maximum width of the lines.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the number of rows needed.
+    pub fn rows(&self) -> u32 {
+        self.lines.len() as u32
+    }
+
+    /// Returns the number of columns of the widest line.
+    pub fn cols(&self) -> u32 {
+        self.lines.iter().map(|l| l.cols).max().unwrap_or(0)
+    }
+
+    /// Returns the number of rows and columns needed.
+    pub fn dim_yx(&self) -> (u32, u32) {
+        (self.rows(), self.cols())
+    }
+
+    /// Draws the lines in the `plane`, with the layout starting at the row `y`,
+    /// or at the cursor row if `None`, and at the column `x`.
+    ///
+    /// If the plane has scrolling enabled it's scrolled when the text reaches
+    /// its bottom, otherwise it's an error.
+    ///
+    /// Returns the number of rows written.
+    ///
+    /// *(No equivalent C style function)*
+    pub fn draw(&self, plane: &mut NcPlane, y: Option<u32>, x: u32) -> NcResult<u32> {
+        let dim_y = plane.dim_y();
+        let mut row = y.unwrap_or_else(|| plane.cursor_y());
+        for line in &self.lines {
+            if row >= dim_y {
+                if !plane.scrolling_p() {
+                    return Err(NcError::with_kind(
+                        NcErrorKind::InvalidArgument,
+                        &format!["NcTextLayout.draw(): row {} is out of the plane", row],
+                    ));
+                }
+                plane.scrollup(row + 1 - dim_y)?;
+                row = dim_y - 1;
+            }
+            plane.cursor_move_yx(row, x + line.x)?;
+            for (span, text) in &line.fragments {
+                self.spans[*span].writer(plane).put(text)?;
+            }
+            row += 1;
+        }
+        Ok(self.rows())
+    }
+}
+
+/// # Methods
+impl NcTextLine {
+    /// Returns the text of the line.
+    pub fn text(&self) -> String {
+        self.fragments.iter().map(|(_, t)| t.as_str()).collect()
+    }
+
+    fn push(&mut self, span: usize, cluster: &str, cols: u8) {
+        match self.fragments.last_mut() {
+            Some((last, text)) if *last == span => text.push_str(cluster),
+            _ => self.fragments.push((span, cluster.to_owned())),
+        }
+        self.cols += cols as u32;
+    }
+}
+
+/// A grapheme cluster of a word, with the span where it starts,
+/// and the columns it occupies.
+type Cluster = (usize, String, u8);
+
+/// Breaks the text of the `spans` into lines of up to `width` columns.
+fn break_lines(spans: &[NcSpan], width: u32) -> Vec<NcTextLine> {
+    let mut lines = vec![NcTextLine::default()];
+    // the spaces and the grapheme clusters of the word being read
+    let mut spaces = vec![];
+    let mut word: Vec<Cluster> = vec![];
+    let mut breaker = GraphemeBreaker::default();
+    for (i, span) in spans.iter().enumerate() {
+        for c in span.text.chars() {
+            let joins = breaker.joins(c);
+            if c == '\n' {
+                place_word(&mut lines, &mut spaces, &mut word, width);
+                spaces.clear();
+                lines.push(NcTextLine::default());
+            } else if c.is_whitespace() {
+                place_word(&mut lines, &mut spaces, &mut word, width);
+                spaces.push(i);
+            } else {
+                let cols = char_width(c);
+                match word.last_mut() {
+                    Some((_, cluster, _)) if joins || cols == 0 => cluster.push(c),
+                    _ => word.push((i, c.to_string(), cols)),
+                }
+            }
+        }
+    }
+    place_word(&mut lines, &mut spaces, &mut word, width);
+    // a final newline doesn't start another line, and no text needs no lines
+    if lines
+        .last()
+        .map_or(false, |l| l.fragments.is_empty() && !l.wrapped)
+    {
+        lines.pop();
+    }
+    lines
+}
+
+/// Places a `word`, preceded by some `spaces`, at the end of the `lines`.
+fn place_word(
+    lines: &mut Vec<NcTextLine>,
+    spaces: &mut Vec<usize>,
+    word: &mut Vec<Cluster>,
+    width: u32,
+) {
+    if word.is_empty() {
+        return;
+    }
+    let word_cols: u32 = word.iter().map(|&(_, _, cols)| cols as u32).sum();
+    let line = lines.last_mut().expect("a line");
+    let fits = line.cols + spaces.len() as u32 + word_cols <= width;
+    if fits && !(line.wrapped && line.cols == 0) {
+        for &span in spaces.iter() {
+            line.push(span, " ", 1);
+        }
+    } else if !fits && line.cols > 0 {
+        lines.push(NcTextLine { wrapped: true, ..NcTextLine::default() });
+    }
+    for (span, cluster, cols) in word.iter() {
+        let line = lines.last_mut().expect("a line");
+        if line.cols + *cols as u32 > width && line.cols > 0 {
+            lines.push(NcTextLine { wrapped: true, ..NcTextLine::default() });
+        }
+        lines
+            .last_mut()
+            .expect("a line")
+            .push(*span, cluster, *cols);
+    }
+    spaces.clear();
+    word.clear();
+}
+
+#[cfg(test)]
+mod test {
+    use super::NcTextLayout;
+    use crate::{NcAlign, NcMarkup};
+
+    fn texts(layout: &NcTextLayout) -> Vec<String> {
+        layout.lines().iter().map(|l| l.text()).collect()
+    }
+
+    #[test]
+    fn break_words() {
+        let markup = NcMarkup::parse("one [b]two[/] three\nfourfivesix").unwrap();
+        let layout = NcTextLayout::with_markup(&markup, 9, NcAlign::Left);
+        assert_eq![vec!["one two", "three", "fourfives", "ix"], texts(&layout)];
+        assert_eq![(4, 9), layout.dim_yx()];
+        // the bold word keeps its own span
+        assert_eq![(1, "two".to_owned()), layout.lines()[0].fragments[1]];
+    }
+
+    #[test]
+    fn align_lines() {
+        let layout = NcTextLayout::new("ab cdef", 5, NcAlign::Right);
+        assert_eq![
+            vec![3, 1],
+            layout.lines().iter().map(|l| l.x).collect::<Vec<_>>()
+        ];
+        let layout = NcTextLayout::new("ab cdef", 5, NcAlign::Center);
+        assert_eq![
+            vec![1, 0],
+            layout.lines().iter().map(|l| l.x).collect::<Vec<_>>()
+        ];
+    }
+
+    #[test]
+    fn break_grapheme_clusters() {
+        // a wide character, and a combining accent that doesn't add columns
+        let layout = NcTextLayout::new("字字e\u{301}x", 5, NcAlign::Left);
+        assert_eq![vec!["字字e\u{301}", "x"], texts(&layout)];
+        assert_eq![
+            vec![5, 1],
+            layout.lines().iter().map(|l| l.cols).collect::<Vec<_>>()
+        ];
+
+        // a flag and a zwj sequence are never split
+        let flag = "\u{1f1ea}\u{1f1f8}";
+        let family = "\u{1f469}\u{200d}\u{1f467}";
+        let layout = NcTextLayout::new(&format!["{}{}{}", flag, family, flag], 5, NcAlign::Left);
+        assert_eq![
+            vec![format!["{}{}", flag, family], flag.to_owned()],
+            texts(&layout)
+        ];
+    }
+
+    #[test]
+    fn empty_and_newlines() {
+        assert_eq![0, NcTextLayout::new("", 10, NcAlign::Left).rows()];
+        assert_eq![1, NcTextLayout::new("a\n", 10, NcAlign::Left).rows()];
+        assert_eq![3, NcTextLayout::new("a\n\nb", 10, NcAlign::Left).rows()];
+    }
+}
@@ -0,0 +1,119 @@
+//! Character widths and grapheme clusters
+//
+// These don't depend on the C library nor on the locale, unlike `wcwidth`
+// and `ncstrwidth`, so that the text is measured the same everywhere, even
+// before notcurses sets the locale, or when it's not a UTF-8 one.
+//
+// The widths come from the `unicode-width` crate, and the grapheme clusters
+// from the extended ones of the `unicode-segmentation` crate.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthChar;
+
+/// Returns the number of columns a character occupies in the terminal.
+///
+/// The characters that extend a grapheme cluster, like combining marks and
+/// variation selectors, occupy no columns, like the control and format
+/// characters. East Asian wide and fullwidth characters, and emoji
+/// presented as such by default, occupy two.
+pub(crate) fn char_width(c: char) -> u8 {
+    c.width().unwrap_or(0) as u8
+}
+
+/// Finds the boundaries of the grapheme clusters in a sequence of characters,
+/// fed one by one.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct GraphemeBreaker {
+    /// The current cluster.
+    cluster: String,
+}
+
+impl GraphemeBreaker {
+    /// Returns true if the character `c` continues the current cluster,
+    /// or false if it starts a new one.
+    pub(crate) fn joins(&mut self, c: char) -> bool {
+        let start = self.cluster.len();
+        self.cluster.push(c);
+        let joins = start > 0
+            && self
+                .cluster
+                .grapheme_indices(true)
+                .next_back()
+                .map(|(i, _)| i)
+                == Some(0);
+        if !joins {
+            self.cluster.drain(..start);
+        }
+        joins
+    }
+
+    /// Forgets the current cluster.
+    pub(crate) fn reset(&mut self) {
+        self.cluster.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{char_width, GraphemeBreaker};
+
+    #[test]
+    fn widths() {
+        assert_eq![1, char_width('a')];
+        assert_eq![1, char_width('é')];
+        assert_eq![0, char_width('\u{301}')];
+        assert_eq![0, char_width('\u{200d}')];
+        assert_eq![0, char_width('\u{fe0f}')];
+        assert_eq![0, char_width('\t')];
+        assert_eq![2, char_width('字')];
+        assert_eq![2, char_width('Ａ')];
+        assert_eq![1, char_width('ｶ')];
+        assert_eq![2, char_width('한')];
+        assert_eq![2, char_width('😀')];
+        // a lone regional indicator is neither wide nor an emoji
+        assert_eq![1, char_width('\u{1f1ea}')];
+    }
+
+    #[test]
+    fn grapheme_clusters() {
+        fn clusters(s: &str) -> Vec<String> {
+            let mut breaker = GraphemeBreaker::default();
+            let mut clusters: Vec<String> = vec![];
+            for c in s.chars() {
+                match clusters.last_mut() {
+                    Some(cluster) if breaker.joins(c) => cluster.push(c),
+                    Some(_) => clusters.push(c.to_string()),
+                    None => {
+                        breaker.joins(c);
+                        clusters.push(c.to_string());
+                    }
+                }
+            }
+            clusters
+        }
+
+        // combining marks and variation selectors
+        assert_eq![
+            vec!["e\u{301}", "x", "\u{2764}\u{fe0f}"],
+            clusters("e\u{301}x\u{2764}\u{fe0f}")
+        ];
+        // regional indicators, in pairs
+        assert_eq![
+            vec!["\u{1f1ea}\u{1f1f8}", "\u{1f1eb}\u{1f1f7}", "\u{1f1ee}"],
+            clusters("\u{1f1ea}\u{1f1f8}\u{1f1eb}\u{1f1f7}\u{1f1ee}")
+        ];
+        // a zwj sequence with an emoji modifier
+        let family = "\u{1f469}\u{1f3fd}\u{200d}\u{1f467}";
+        assert_eq![vec![family, "a"], clusters(&format!["{}a", family])];
+        // a joiner after a letter doesn't join the next emoji
+        assert_eq![
+            vec!["a\u{200d}", "\u{1f467}"],
+            clusters("a\u{200d}\u{1f467}")
+        ];
+        // hangul jamo, and a carriage return with its line feed
+        assert_eq![
+            vec!["\u{1100}\u{1161}\u{11a8}", "\r\n", "b"],
+            clusters("\u{1100}\u{1161}\u{11a8}\r\nb")
+        ];
+    }
+}
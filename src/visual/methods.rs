@@ -5,8 +5,10 @@ use libc::c_void;
 
 use crate::{
     c_api::{self, NcChannel_u32, NcResult_i32, NCRESULT_ERR},
-    cstring, error, error_ref_mut, Nc, NcBlitter, NcChannel, NcDirect, NcError, NcPixel, NcPlane,
-    NcResult, NcRgba, NcScale, NcTime, NcVisual, NcVisualGeometry, NcVisualOptions,
+    cstring, error,
+    error::clear_errno,
+    error_ref_mut, Nc, NcBlitter, NcChannel, NcDirect, NcError, NcPixel, NcPlane, NcResult, NcRgba,
+    NcScale, NcTime, NcVisual, NcVisualGeometry, NcVisualOptions,
 };
 
 /// # NcVisual Constructors & destructors
@@ -518,8 +520,9 @@ impl NcVisual {
     /// *C style function: [ncvisual_at_yx()][c_api::ncvisual_at_yx].*
     pub fn at_yx(&self, y: u32, x: u32) -> NcResult<NcPixel> {
         let mut pixel = 0;
+        clear_errno();
         let res = unsafe { c_api::ncvisual_at_yx(self, y, x, &mut pixel) };
-        error![res, "NcVisual.at_yx()", pixel.into()]
+        error![@fn "ncvisual_at_yx", res, "NcVisual.at_yx()", pixel.into()]
     }
 
     /// Sets the specified pixel.
@@ -672,7 +675,8 @@ impl NcVisual {
     ) -> NcResult<NcVisualGeometry> {
         let mut geom = c_api::NcVGeom::new();
 
+        clear_errno();
         let res = unsafe { c_api::ncdirectf_geom(ncd, self, options, &mut geom) };
-        error![res, "NcVisual.ncdirectf_geom()", geom.into()];
+        error![@fn "ncdirectf_geom", res, "NcVisual.ncdirectf_geom()", geom.into()];
     }
 }
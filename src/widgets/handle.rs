@@ -0,0 +1,321 @@
+//! `NcWidgetHandle`
+
+use core::{marker::PhantomData, ptr::NonNull};
+
+use crate::{
+    c_api,
+    error::clear_errno,
+    widgets::{
+        NcMenu, NcMenuOptions, NcProgBar, NcProgBarOptions, NcReader, NcReaderOptions, NcSelector,
+        NcSelectorItem, NcSelectorOptions, NcTabbed, NcTabbedOptions, NcTree, NcTreeItem,
+        NcTreeOptions,
+    },
+    NcError, NcErrorKind, NcInput, NcPlane, NcPlaneHandle, NcResult,
+};
+
+/// A widget that can be owned by an [`NcWidgetHandle`].
+pub trait NcWidget {
+    /// Destroys the widget, along with the plane it owns.
+    ///
+    /// # Safety
+    /// The widget must not be used afterwards.
+    unsafe fn destroy_widget(&mut self);
+
+    /// Returns the plane the widget owns, whose data is freed before
+    /// destroying it.
+    fn widget_plane(&mut self) -> *mut NcPlane;
+}
+
+impl NcWidget for NcMenu {
+    unsafe fn destroy_widget(&mut self) {
+        self.destroy();
+    }
+
+    fn widget_plane(&mut self) -> *mut NcPlane {
+        unsafe { c_api::ncmenu_plane(self) }
+    }
+}
+
+impl NcWidget for NcProgBar {
+    unsafe fn destroy_widget(&mut self) {
+        self.destroy();
+    }
+
+    fn widget_plane(&mut self) -> *mut NcPlane {
+        unsafe { c_api::ncprogbar_plane(self) }
+    }
+}
+
+impl NcWidget for NcReader {
+    unsafe fn destroy_widget(&mut self) {
+        self.destroy();
+    }
+
+    fn widget_plane(&mut self) -> *mut NcPlane {
+        unsafe { c_api::ncreader_plane(self) }
+    }
+}
+
+impl NcWidget for NcSelector {
+    unsafe fn destroy_widget(&mut self) {
+        let _ = self.destroy();
+    }
+
+    fn widget_plane(&mut self) -> *mut NcPlane {
+        unsafe { c_api::ncselector_plane(self) }
+    }
+}
+
+impl NcWidget for NcTabbed {
+    unsafe fn destroy_widget(&mut self) {
+        self.destroy();
+    }
+
+    fn widget_plane(&mut self) -> *mut NcPlane {
+        unsafe { c_api::nctabbed_plane(self) }
+    }
+}
+
+impl NcWidget for NcTree {
+    unsafe fn destroy_widget(&mut self) {
+        self.destroy();
+    }
+
+    fn widget_plane(&mut self) -> *mut NcPlane {
+        unsafe { c_api::nctree_plane(self) }
+    }
+}
+
+/// An owned widget, that destroys itself and its plane when dropped.
+///
+/// Widgets take ownership of the [`NcPlane`] they are created on, destroying
+/// it along with themselves, or right away if the creation fails. That's why
+/// their constructors take the [`NcPlaneHandle`] by value, and they return an
+/// error for the standard plane, which can't be destroyed. The widget keeps
+/// the plane's lifetime, so it can't outlive its context or parent plane.
+///
+/// The exception is [`NcMenu`], which creates its own plane bound to the one
+/// it receives, and so it only borrows it.
+///
+/// It dereferences to the wrapped widget, so all of its methods taking a
+/// shared reference are available. Of the methods taking an exclusive
+/// reference, it only provides the ones that can't destroy the widget nor
+/// its plane. The rest can be reached through the unsafe
+/// [`widget_mut`][NcWidgetHandle#method.widget_mut].
+#[derive(Debug)]
+pub struct NcWidgetHandle<'a, W: NcWidget> {
+    widget: NonNull<W>,
+    _plane: PhantomData<NcPlaneHandle<'a>>,
+}
+
+/// An owned [`NcMenu`].
+pub type NcMenuHandle<'a> = NcWidgetHandle<'a, NcMenu>;
+/// An owned [`NcProgBar`].
+pub type NcProgBarHandle<'a> = NcWidgetHandle<'a, NcProgBar>;
+/// An owned [`NcReader`].
+pub type NcReaderHandle<'a> = NcWidgetHandle<'a, NcReader>;
+/// An owned [`NcSelector`].
+pub type NcSelectorHandle<'a> = NcWidgetHandle<'a, NcSelector>;
+/// An owned [`NcTabbed`].
+pub type NcTabbedHandle<'a> = NcWidgetHandle<'a, NcTabbed>;
+/// An owned [`NcTree`].
+pub type NcTreeHandle<'a> = NcWidgetHandle<'a, NcTree>;
+
+/// # `NcWidgetHandle` methods
+impl<'a, W: NcWidget> NcWidgetHandle<'a, W> {
+    /// Wraps a widget pointer returned by the C `function`,
+    /// returning an error with `msg` if it's null.
+    fn from_ptr(ptr: *mut W, function: &'static str, msg: &str) -> NcResult<Self> {
+        NonNull::new(ptr)
+            .map(|widget| Self { widget, _plane: PhantomData })
+            .ok_or_else(|| NcError::null_returned(function, msg))
+    }
+
+    /// Returns an error if the `plane` is the standard plane,
+    /// which a widget can't take ownership of.
+    fn check_plane(plane: &NcPlaneHandle<'_>, msg: &str) -> NcResult<()> {
+        if plane.is_stdplane() {
+            return Err(NcError::with_kind(
+                NcErrorKind::InvalidArgument,
+                &format!["{}: the standard plane can't be owned by a widget", msg],
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the raw pointer to the wrapped widget.
+    pub fn as_ptr(&self) -> *mut W {
+        self.widget.as_ptr()
+    }
+
+    /// Returns an exclusive reference to the wrapped widget.
+    ///
+    /// # Safety
+    /// The widget and its plane must not be destroyed through the reference,
+    /// since the handle destroys them when dropped.
+    pub unsafe fn widget_mut(&mut self) -> &mut W {
+        self.widget.as_mut()
+    }
+
+    /// Destroys the widget and its plane, consuming the handle.
+    ///
+    /// This is the same as dropping it.
+    pub fn destroy(self) {
+        drop(self)
+    }
+}
+
+/// # `NcMenuHandle` constructors
+impl<'a> NcWidgetHandle<'a, NcMenu> {
+    /// Creates a new menu on a new plane bound to `parent`.
+    ///
+    /// *C style function: [ncmenu_create()][c_api::ncmenu_create].*
+    pub fn new(parent: &'a NcPlaneHandle<'_>, options: &NcMenuOptions) -> NcResult<Self> {
+        clear_errno();
+        let ptr = unsafe { c_api::ncmenu_create(parent.as_ptr(), options) };
+        Self::from_ptr(ptr, "ncmenu_create", "NcMenuHandle::new()")
+    }
+}
+
+/// # `NcProgBarHandle` constructors
+impl<'a> NcWidgetHandle<'a, NcProgBar> {
+    /// Creates a new progress bar, taking ownership of the `plane`.
+    ///
+    /// *C style function: [ncprogbar_create()][c_api::ncprogbar_create].*
+    pub fn new(plane: NcPlaneHandle<'a>, options: &NcProgBarOptions) -> NcResult<Self> {
+        Self::check_plane(&plane, "NcProgBarHandle::new()")?;
+        clear_errno();
+        let ptr = unsafe { c_api::ncprogbar_create(plane.as_ptr(), options) };
+        Self::from_ptr(ptr, "ncprogbar_create", "NcProgBarHandle::new()")
+    }
+}
+
+/// # `NcReaderHandle` constructors
+impl<'a> NcWidgetHandle<'a, NcReader> {
+    /// Creates a new reader, taking ownership of the `plane`.
+    ///
+    /// *C style function: [ncreader_create()][c_api::ncreader_create].*
+    pub fn new(plane: NcPlaneHandle<'a>, options: &NcReaderOptions) -> NcResult<Self> {
+        Self::check_plane(&plane, "NcReaderHandle::new()")?;
+        clear_errno();
+        let ptr = unsafe { c_api::ncreader_create(plane.as_ptr(), options) };
+        Self::from_ptr(ptr, "ncreader_create", "NcReaderHandle::new()")
+    }
+}
+
+/// # `NcSelectorHandle` constructors
+impl<'a> NcWidgetHandle<'a, NcSelector> {
+    /// Creates a new selector, taking ownership of the `plane`.
+    ///
+    /// *C style function: [ncselector_create()][c_api::ncselector_create].*
+    pub fn new(plane: NcPlaneHandle<'a>, options: &NcSelectorOptions) -> NcResult<Self> {
+        Self::check_plane(&plane, "NcSelectorHandle::new()")?;
+        clear_errno();
+        let ptr = unsafe { c_api::ncselector_create(plane.as_ptr(), options) };
+        Self::from_ptr(ptr, "ncselector_create", "NcSelectorHandle::new()")
+    }
+}
+
+/// # `NcTabbedHandle` constructors
+impl<'a> NcWidgetHandle<'a, NcTabbed> {
+    /// Creates a new tabbed widget, taking ownership of the `plane`.
+    ///
+    /// *C style function: [nctabbed_create()][c_api::nctabbed_create].*
+    pub fn new(plane: NcPlaneHandle<'a>, options: &NcTabbedOptions) -> NcResult<Self> {
+        Self::check_plane(&plane, "NcTabbedHandle::new()")?;
+        clear_errno();
+        let ptr = unsafe { c_api::nctabbed_create(plane.as_ptr(), options) };
+        Self::from_ptr(ptr, "nctabbed_create", "NcTabbedHandle::new()")
+    }
+}
+
+/// # `NcTreeHandle` constructors
+impl<'a> NcWidgetHandle<'a, NcTree> {
+    /// Creates a new tree, taking ownership of the `plane`.
+    ///
+    /// *C style function: [nctree_create()][c_api::nctree_create].*
+    pub fn new(plane: NcPlaneHandle<'a>, options: &NcTreeOptions) -> NcResult<Self> {
+        Self::check_plane(&plane, "NcTreeHandle::new()")?;
+        clear_errno();
+        let ptr = unsafe { c_api::nctree_create(plane.as_ptr(), options) };
+        Self::from_ptr(ptr, "nctree_create", "NcTreeHandle::new()")
+    }
+}
+
+/// Forwards methods of a widget taking an exclusive reference.
+macro_rules! forward_widget_mut {
+    ($widget:ident { $( fn $name:ident ($($arg:ident: $ty:ty),*) $(-> $ret:ty)?; )* }) => {
+        impl<'a> NcWidgetHandle<'a, $widget> {
+            $(
+                #[doc = concat!("See [`", stringify!($widget), "::", stringify!($name), "`][",
+                    stringify!($widget), "#method.", stringify!($name), "].")]
+                pub fn $name(&mut self, $($arg: $ty),*) $(-> $ret)? {
+                    unsafe { self.widget.as_mut() }.$name($($arg),*)
+                }
+            )*
+        }
+    };
+}
+
+forward_widget_mut! { NcMenu {
+    fn item_set_status(section: &str, item: &str, enabled: bool) -> NcResult<()>;
+    fn nextitem() -> NcResult<()>;
+    fn nextsection() -> NcResult<()>;
+    fn offer_input(input: NcInput) -> bool;
+    fn plane() -> NcResult<&NcPlane>;
+    fn previtem() -> NcResult<()>;
+    fn prevsection() -> NcResult<()>;
+    fn rollup() -> NcResult<()>;
+    fn selected(shortcut: Option<&mut NcInput>) -> Option<String>;
+    fn unroll(sectionindex: u32) -> NcResult<()>;
+}}
+
+forward_widget_mut! { NcProgBar {
+    fn set_progress(progress: f64) -> NcResult<()>;
+}}
+
+forward_widget_mut! { NcSelector {
+    fn offer_input(input: NcInput) -> bool;
+    fn additem(item: NcSelectorItem) -> NcResult<i32>;
+    fn delitem(item: &str) -> NcResult<i32>;
+    fn selected() -> Option<String>;
+    fn nextitem() -> NcResult<String>;
+    fn previtem() -> NcResult<String>;
+}}
+
+forward_widget_mut! { NcTree {
+    fn add(spec: &u32, item: &NcTreeItem) -> NcResult<()>;
+    fn del(spec: &u32) -> NcResult<()>;
+    fn focused() -> NcResult<&mut NcTreeItem>;
+    fn next() -> NcResult<&mut NcTreeItem>;
+    fn prev() -> NcResult<&mut NcTreeItem>;
+    fn offer_input(input: NcInput) -> bool;
+    fn plane() -> NcResult<&NcPlane>;
+    fn redraw() -> NcResult<()>;
+}}
+
+mod std_impls {
+    use super::{NcWidget, NcWidgetHandle};
+    use crate::plane::user_data::free_plane_data;
+    use core::ops::Deref;
+
+    impl<'a, W: NcWidget> Deref for NcWidgetHandle<'a, W> {
+        type Target = W;
+
+        fn deref(&self) -> &W {
+            unsafe { self.widget.as_ref() }
+        }
+    }
+
+    /// Frees the data of the widget's plane, and destroys them both.
+    impl<'a, W: NcWidget> Drop for NcWidgetHandle<'a, W> {
+        fn drop(&mut self) {
+            let widget = unsafe { self.widget.as_mut() };
+            let plane = widget.widget_plane();
+            if !plane.is_null() {
+                free_plane_data(plane);
+            }
+            unsafe { widget.destroy_widget() };
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! `NcWidgetHarness`
+
+use crate::{
+    c_api,
+    widgets::{NcMenu, NcReader, NcSelector, NcTree, NcWidget, NcWidgetHandle},
+    NcInput, NcInputSource, NcInputType, NcKey, NcKeyMod, NcPlane, NcReceived, NcResult, NcTime,
+};
+
+/// A widget that accepts input, and draws on a plane.
+///
+/// It's driven by an [`NcWidgetHarness`].
+pub trait NcInputWidget {
+    /// Offers an input to the widget, returning true if it was relevant.
+    fn offer_input(&mut self, input: NcInput) -> bool;
+
+    /// Returns the plane the widget draws on.
+    fn widget_plane(&mut self) -> &mut NcPlane;
+
+    /// Reads the next input from `source`, waiting for it up to `time`,
+    /// and offers it to the widget.
+    ///
+    /// Returns whether it was relevant, or `None` if there was no input in
+    /// time or the source is exhausted.
+    fn offer_next<S: NcInputSource + ?Sized>(
+        &mut self,
+        source: &mut S,
+        time: Option<NcTime>,
+    ) -> NcResult<Option<bool>>
+    where
+        Self: Sized,
+    {
+        Ok(read_input(source, time)?.map(|input| self.offer_input(input)))
+    }
+}
+
+/// Reads the next input from `source`, waiting for it up to `time`.
+///
+/// Returns `None` if there was no input in time or the source is exhausted.
+fn read_input<S: NcInputSource + ?Sized>(
+    source: &mut S,
+    time: Option<NcTime>,
+) -> NcResult<Option<NcInput>> {
+    let mut input = NcInput::new_empty();
+    match source.get(time, Some(&mut input))? {
+        NcReceived::NoInput | NcReceived::Event(NcKey::Eof) => Ok(None),
+        _ => Ok(Some(input)),
+    }
+}
+
+impl NcInputWidget for NcMenu {
+    fn offer_input(&mut self, input: NcInput) -> bool {
+        NcMenu::offer_input(self, input)
+    }
+
+    fn widget_plane(&mut self) -> &mut NcPlane {
+        unsafe { &mut *c_api::ncmenu_plane(self) }
+    }
+}
+
+impl NcInputWidget for NcReader {
+    fn offer_input(&mut self, input: NcInput) -> bool {
+        unsafe { c_api::ncreader_offer_input(self, &input) }
+    }
+
+    fn widget_plane(&mut self) -> &mut NcPlane {
+        unsafe { &mut *c_api::ncreader_plane(self) }
+    }
+}
+
+impl NcInputWidget for NcSelector {
+    fn offer_input(&mut self, input: NcInput) -> bool {
+        NcSelector::offer_input(self, input)
+    }
+
+    fn widget_plane(&mut self) -> &mut NcPlane {
+        unsafe { &mut *c_api::ncselector_plane(self) }
+    }
+}
+
+impl NcInputWidget for NcTree {
+    fn offer_input(&mut self, input: NcInput) -> bool {
+        NcTree::offer_input(self, input)
+    }
+
+    fn widget_plane(&mut self) -> &mut NcPlane {
+        unsafe { &mut *c_api::nctree_plane(self) }
+    }
+}
+
+/// Drives a widget with synthetic input, and captures what it draws.
+///
+/// It builds the [`NcInput`]s for typing text, pressing keys and clicking,
+/// offers them to the widget and reports whether the widget found them
+/// relevant. Then the contents of the widget's plane can be inspected,
+/// without needing to render them.
+///
+/// Mouse coordinates are absolute, like the ones received from the terminal.
+///
+/// # Example
+/// ```no_run
+/// # use libnotcurses_sys::{*, widgets::*};
+/// fn test_selector(selector: &mut NcSelector) {
+///     let mut harness = NcWidgetHarness::new(selector);
+///     assert![harness.press(NcKey::Down, NcKeyMod::None)];
+///     assert![harness.lines()[2].contains("option2")];
+/// }
+/// ```
+#[derive(Debug)]
+pub struct NcWidgetHarness<'w, W: NcInputWidget + ?Sized> {
+    widget: &'w mut W,
+}
+
+/// # Constructors
+impl<'w, W: NcInputWidget + ?Sized> NcWidgetHarness<'w, W> {
+    /// New harness driving the `widget`.
+    pub fn new(widget: &'w mut W) -> Self {
+        Self { widget }
+    }
+}
+
+/// # Methods
+impl<'w, W: NcInputWidget + ?Sized> NcWidgetHarness<'w, W> {
+    /// Returns the driven widget.
+    pub fn widget(&self) -> &W {
+        self.widget
+    }
+
+    /// Offers an `input` to the widget, returning true if it was relevant.
+    pub fn offer(&mut self, input: NcInput) -> bool {
+        self.widget.offer_input(input)
+    }
+
+    /// Reads the next input from `source`, waiting for it up to `time`,
+    /// and offers it to the widget.
+    ///
+    /// Returns whether it was relevant, or `None` if there was no input in
+    /// time or the source is exhausted.
+    pub fn offer_next<S: NcInputSource + ?Sized>(
+        &mut self,
+        source: &mut S,
+        time: Option<NcTime>,
+    ) -> NcResult<Option<bool>> {
+        Ok(read_input(source, time)?.map(|input| self.offer(input)))
+    }
+
+    /// Types the characters of `text`, one key press each,
+    /// returning how many of them were relevant to the widget.
+    ///
+    /// Newlines are typed as [`NcKey::Enter`], and tabs as [`NcKey::Tab`].
+    pub fn type_str(&mut self, text: &str) -> usize {
+        let mut relevant = 0;
+        for c in text.chars() {
+            let key = match c {
+                '\n' => NcReceived::Event(NcKey::Enter),
+                '\t' => NcReceived::Event(NcKey::Tab),
+                c => NcReceived::Char(c),
+            };
+            if self.press(key, NcKeyMod::None) {
+                relevant += 1;
+            }
+        }
+        relevant
+    }
+
+    /// Presses a `key` with some `modifiers`,
+    /// returning true if it was relevant to the widget.
+    ///
+    /// Only the press is sent, like most terminals do.
+    pub fn press(&mut self, key: impl Into<NcReceived>, modifiers: NcKeyMod) -> bool {
+        self.offer(NcInput::with_key(key, modifiers, NcInputType::Press))
+    }
+
+    /// Releases a `key` with some `modifiers`,
+    /// returning true if it was relevant to the widget.
+    pub fn release(&mut self, key: impl Into<NcReceived>, modifiers: NcKeyMod) -> bool {
+        self.offer(NcInput::with_key(key, modifiers, NcInputType::Release))
+    }
+
+    /// Clicks the primary button at the absolute coordinates `y`, `x`,
+    /// returning true if the press or the release was relevant to the widget.
+    pub fn click(&mut self, y: i32, x: i32) -> bool {
+        self.click_button(NcKey::Button1, y, x, NcKeyMod::None)
+    }
+
+    /// Clicks a mouse `button` with some `modifiers` at the absolute
+    /// coordinates `y`, `x`, returning true if the press or the release
+    /// was relevant to the widget.
+    pub fn click_button(&mut self, button: NcKey, y: i32, x: i32, modifiers: NcKeyMod) -> bool {
+        let mut input = NcInput::with_mouse(button, y, x, modifiers, NcInputType::Press);
+        let press = self.offer(input);
+        input.evtype = NcInputType::Release.into();
+        let release = self.offer(input);
+        press || release
+    }
+
+    /// Moves the scroll wheel at the absolute coordinates `y`, `x`,
+    /// by a number of `rows`, negative upwards, returning how many of the
+    /// steps were relevant to the widget.
+    pub fn scroll(&mut self, y: i32, x: i32, rows: i32) -> usize {
+        let button = if rows < 0 { NcKey::Button4 } else { NcKey::Button5 };
+        let input = NcInput::with_mouse(button, y, x, NcKeyMod::None, NcInputType::Press);
+        let mut relevant = 0;
+        for _ in 0..rows.abs() {
+            if self.offer(input) {
+                relevant += 1;
+            }
+        }
+        relevant
+    }
+
+    /// Returns the plane the widget draws on.
+    pub fn plane(&mut self) -> &NcPlane {
+        self.widget.widget_plane()
+    }
+
+    /// Returns the contents of the widget's plane, as a single string.
+    pub fn contents(&mut self) -> String {
+        self.widget
+            .widget_plane()
+            .contents(Some(0), Some(0), None, None)
+    }
+
+    /// Returns the contents of each row of the widget's plane,
+    /// without the trailing whitespace.
+    pub fn lines(&mut self) -> Vec<String> {
+        let plane = self.widget.widget_plane();
+        (0..plane.dim_y())
+            .map(|y| {
+                let row = plane.contents(Some(y), Some(0), Some(1), None);
+                row.trim_end().to_owned()
+            })
+            .collect()
+    }
+}
+
+/// # `NcWidgetHandle` harness
+impl<'a, W: NcWidget + NcInputWidget> NcWidgetHandle<'a, W> {
+    /// Returns a harness driving this widget.
+    pub fn harness(&mut self) -> NcWidgetHarness<'_, W> {
+        // the harness doesn't expose exclusive references to the widget
+        // nor to its plane, so they can't be destroyed through it
+        NcWidgetHarness::new(unsafe { self.widget_mut() })
+    }
+}
@@ -1,5 +1,7 @@
 //! The notcurses widgets.
 
+pub(crate) mod handle;
+pub(crate) mod harness;
 pub(crate) mod menu;
 pub(crate) mod multiselector;
 pub(crate) mod plot;
@@ -10,6 +12,10 @@ pub(crate) mod selector;
 pub(crate) mod tabbed;
 pub(crate) mod tree;
 
+mod test;
+
+pub use handle::*;
+pub use harness::{NcInputWidget, NcWidgetHarness};
 pub use menu::*;
 pub use multiselector::*;
 pub use plot::*;
@@ -1,7 +1,9 @@
 //! `NcReader*` methods and associated functions.
 
+use core::ptr::null_mut;
+
 use super::{NcReader, NcReaderOptions};
-use crate::{c_api::ncreader_create, error_ref_mut, NcPlane, NcResult};
+use crate::{c_api, c_api::ncreader_create, error_ref_mut, NcPlane, NcResult};
 
 /// # `NcReaderOptions` Constructors
 impl NcReaderOptions {
@@ -18,7 +20,7 @@ impl NcReaderOptions {
     }
 }
 
-/// # `NcReader` Constructors
+/// # `NcReader` Constructors and destructors
 impl NcReader {
     /// `NcReader` simple constructor.
     pub fn new<'a>(plane: &mut NcPlane) -> NcResult<&'a mut Self> {
@@ -32,4 +34,11 @@ impl NcReader {
     ) -> NcResult<&'a mut Self> {
         error_ref_mut![unsafe { ncreader_create(plane, options) }]
     }
+
+    /// Destroys the `NcReader` and its underlying [`NcPlane`].
+    ///
+    /// *C style function: [ncreader_destroy()][c_api::ncreader_destroy].*
+    pub fn destroy(&mut self) {
+        unsafe { c_api::ncreader_destroy(self, null_mut()) };
+    }
 }
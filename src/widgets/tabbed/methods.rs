@@ -1,35 +1,56 @@
-//! `NcTabe` & `NcTabbed*` methods and associated functions.
+//! `NcTab` & `NcTabbed*` methods and associated functions.
 
-use super::{NcTab, NcTabbed, NcTabbedOptions};
-// use crate::{error_ref_mut, ncreader_create, NcPlane, NcResult};
+use core::ptr::null;
+
+use super::{NcTabbed, NcTabbedOptions};
+use crate::{c_api, error_ref_mut, NcPlane, NcResult};
 
 /// # `NcTabbedOptions` Constructors
 impl NcTabbedOptions {
     /// `NcTabbedOptions` simple constructor
     pub const fn new() -> Self {
         Self {
-            // channels used for input
-            tchannels: 0,
-            // attributes used for input
-            tattrword: 0,
-            // bitfield of NCREADER_OPTION_*
+            // channel for the selected tab header
+            selchan: 0,
+            // channel for unselected tab headers
+            hdrchan: 0,
+            // channel for the tab separator
+            sepchan: 0,
+            // separator string (copied by nctabbed_create())
+            separator: null(),
+            // bitmask of NCTABBED_OPTION_*
             flags: 0,
         }
     }
 }
 
-/// # `NcTabbed` Constructors
+/// # `NcTabbed` Constructors and destructors
 impl NcTabbed {
-    /// `NcTabbed` simple constructor
+    /// `NcTabbed` simple constructor.
+    ///
+    /// Takes ownership of the `plane`, which will be destroyed by
+    /// [destroy][NcTabbed#method.destroy](), or on error.
     pub fn new<'a>(plane: &mut NcPlane) -> NcResult<&'a mut Self> {
-        Self::with_options(plane, NcTabbedOptions::new())
+        Self::with_options(plane, &NcTabbedOptions::new())
     }
 
-    /// `NcTabbed` constructor with options
+    /// `NcTabbed` constructor with options.
+    ///
+    /// *C style function: [nctabbed_create()][c_api::nctabbed_create].*
     pub fn with_options<'a>(
         plane: &mut NcPlane,
-        options: NcTabbedOptions,
+        options: &NcTabbedOptions,
     ) -> NcResult<&'a mut Self> {
-        error_ref_mut![unsafe { nctabbed_create(plane, &options) }]
+        error_ref_mut![
+            unsafe { c_api::nctabbed_create(plane, options) },
+            "NcTabbed.with_options()"
+        ]
+    }
+
+    /// Destroys the `NcTabbed` widget, its tabs and its underlying [`NcPlane`].
+    ///
+    /// *C style function: [nctabbed_destroy()][c_api::nctabbed_destroy].*
+    pub fn destroy(&mut self) {
+        unsafe { c_api::nctabbed_destroy(self) };
     }
 }
@@ -40,7 +40,7 @@
 
 use crate::c_api::ffi;
 
-// mod methods;
+mod methods;
 
 /// A tab for [`NcTabbed`].
 ///
@@ -0,0 +1,80 @@
+//! Test `NcWidgetHarness` and `NcWidgetHandle`s.
+
+use serial_test::serial;
+
+use crate::{
+    notcurses::helpers::with_test_pile,
+    widgets::{
+        NcReaderHandle, NcReaderOptions, NcSelectorHandle, NcSelectorItem, NcSelectorOptions,
+    },
+    NcInputReplayer, NcKey, NcKeyMod, NcString,
+};
+
+#[test]
+#[serial]
+fn harness_reader() -> crate::NcResult<()> {
+    with_test_pile(4, 20, |_, pile| {
+        let plane = pile.new_child_sized(1, 0, 1, 20)?;
+        let mut reader = NcReaderHandle::new(plane, &NcReaderOptions::new())?;
+        let mut harness = reader.harness();
+
+        assert_eq![5, harness.type_str("hello")];
+        assert_eq![vec!["hello".to_owned()], harness.lines()];
+        assert![harness.press(NcKey::Backspace, NcKeyMod::None)];
+        assert![harness.contents().starts_with("hell")];
+        assert![!harness.contents().contains("hello")];
+
+        // the reader ignores the mouse
+        assert![!harness.click(1, 0)];
+
+        // inputs can also be replayed from a recording
+        let recording = "# libnotcurses-sys input recording v2\n0 33 1 0 -1 -1 -1 -1 21\n";
+        let mut replay = NcInputReplayer::new(recording.as_bytes());
+        assert_eq![Some(true), harness.offer_next(&mut replay, None)?];
+        assert_eq![None, harness.offer_next(&mut replay, None)?];
+        assert![harness.contents().starts_with("hell!")];
+        Ok(())
+    })
+}
+
+#[test]
+#[serial]
+fn harness_selector() -> crate::NcResult<()> {
+    with_test_pile(12, 30, |nc, pile| {
+        let strings: Vec<NcString> = ["option1", "desc1", "option2", "desc2", "option3", "desc3"]
+            .iter()
+            .map(|s| NcString::new(s))
+            .collect();
+        let items: Vec<NcSelectorItem> = strings
+            .chunks(2)
+            .map(|pair| NcSelectorItem::new(&pair[0], &pair[1]))
+            .chain(Some(NcSelectorItem::new_empty()))
+            .collect();
+
+        let plane = pile.new_child_sized(0, 0, 12, 30)?;
+        let mut selector = NcSelectorHandle::new(plane, &NcSelectorOptions::new(&items))?;
+        assert_eq![Some("option1".to_owned()), selector.selected()];
+        {
+            let mut harness = selector.harness();
+            assert![harness.contents().contains("option2")];
+            assert![harness.press(NcKey::Down, NcKeyMod::None)];
+        }
+        assert_eq![Some("option2".to_owned()), selector.selected()];
+
+        // clicking an item selects it
+        let (y, x) = {
+            let mut harness = selector.harness();
+            let lines = harness.lines();
+            let y = lines.iter().position(|l| l.contains("option3")).unwrap();
+            (y as i32, lines[y].find("option3").unwrap() as i32)
+        };
+        assert![selector.harness().click(y, x)];
+        assert_eq![Some("option3".to_owned()), selector.selected()];
+
+        // the standard plane can't be owned by a widget
+        let stdplane = nc.stdplane_handle()?;
+        assert![NcReaderHandle::new(stdplane, &NcReaderOptions::new()).is_err()];
+
+        Ok(())
+    })
+}
@@ -0,0 +1,4 @@
+//! Widgets tests.
+
+#[cfg(all(test, unix))]
+mod harness;